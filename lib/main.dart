@@ -3,6 +3,7 @@ import 'package:flutter/services.dart' show rootBundle;
 import 'package:flutter/material.dart';
 import 'package:flutter/scheduler.dart';
 import 'package:dancy/src/rust/api/proxy.dart';
+import 'package:dancy/src/rust/cartridge.dart';
 import 'package:dancy/src/rust/frb_generated.dart';
 
 Future<void> main() async {
@@ -21,6 +22,7 @@ class _GamePageState extends State<GamePage>
     with SingleTickerProviderStateMixin {
   DancyProxy? _proxy;
   ui.Image? _frameImage;
+  String? _loadError;
   late Ticker _ticker;
 
   @override
@@ -36,7 +38,16 @@ class _GamePageState extends State<GamePage>
     final byteData = await rootBundle.load('assets/dmg-acid2.gb');
     final romBytes = byteData.buffer.asUint8List();
 
-    _proxy = await DancyProxy.newInstance(romBytes: romBytes);
+    try {
+      _proxy = await DancyProxy.newInstance(romBytes: romBytes);
+    } on CartridgeError catch (e) {
+      debugPrint('Failed to load ROM: $e');
+      if (mounted) {
+        setState(() {
+          _loadError = 'Cannot load ROM: ${_describe(e)}';
+        });
+      }
+    }
   }
 
   void _gameLoop(Duration elapsed) async {
@@ -59,6 +70,18 @@ class _GamePageState extends State<GamePage>
     });
   }
 
+  // Same wording as the Display impl on the Rust side
+  static String _describe(CartridgeError error) => switch (error) {
+    CartridgeError.tooShort => 'ROM is too short to contain a header',
+    CartridgeError.unsupportedMapper => 'Unsupported cartridge type',
+    CartridgeError.unsupportedRomSize => 'Unsupported ROM size',
+    CartridgeError.unsupportedRamSize => 'Unsupported RAM size',
+    CartridgeError.badHeaderChecksum => 'Header checksum mismatch',
+    CartridgeError.sizeMismatch => 'ROM is smaller than its header says',
+    CartridgeError.badBootRomSize => 'Boot ROM must be 256 or 2304 bytes',
+    CartridgeError.unavailable => 'Emulator is not running',
+  };
+
   @override
   void dispose() {
     _ticker.dispose();
@@ -74,12 +97,17 @@ class _GamePageState extends State<GamePage>
           // Screen
           Expanded(
             child: Center(
-              child: AspectRatio(
-                aspectRatio: 160 / 144,
-                child: RepaintBoundary(
-                  child: CustomPaint(painter: ScreenPainter(_frameImage)),
-                ),
-              ),
+              child: _loadError != null
+                  ? Text(
+                      _loadError!,
+                      style: const TextStyle(color: Colors.white),
+                    )
+                  : AspectRatio(
+                      aspectRatio: 160 / 144,
+                      child: RepaintBoundary(
+                        child: CustomPaint(painter: ScreenPainter(_frameImage)),
+                      ),
+                    ),
             ),
           ),
         ],
@@ -3,18 +3,80 @@
 
 // ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
 
+import '../cartridge.dart';
+import '../cartridge/header.dart';
 import '../frb_generated.dart';
+import '../savestate.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
 // These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `EmuCommand`
 
 // Rust type: RustOpaqueMoi<flutter_rust_bridge::for_generated::RustAutoOpaqueInner<DancyProxy>>
 abstract class DancyProxy implements RustOpaqueInterface {
+  Future<Int16List> getAudioSamples();
+
+  Future<bool> getRumble();
+
+  Future<Uint8List> getSaveData();
+
+  Future<bool> isSaveDirty();
+
+  Future<void> loadState({required List<int> data});
+
   // HINT: Make it `#[frb(sync)]` to let it become the default constructor of Dart class.
   static Future<DancyProxy> newInstance({required List<int> romBytes}) =>
       RustLib.instance.api.crateApiProxyDancyProxyNew(romBytes: romBytes);
 
+  static Future<DancyProxy> newWithSaveData({
+    required List<int> romBytes,
+    required List<int> saveData,
+  }) => RustLib.instance.api.crateApiProxyDancyProxyNewWithSaveData(
+    romBytes: romBytes,
+    saveData: saveData,
+  );
+
+  static Future<DancyProxy> newWithBootRom({
+    required List<int> romBytes,
+    required List<int> saveData,
+    required List<int> bootRom,
+  }) => RustLib.instance.api.crateApiProxyDancyProxyNewWithBootRom(
+    romBytes: romBytes,
+    saveData: saveData,
+    bootRom: bootRom,
+  );
+
+  Future<Uint8List> rewindFrame();
+
+  static Future<CartridgeHeader> romInfo({required List<int> romBytes}) =>
+      RustLib.instance.api.crateApiProxyDancyProxyRomInfo(romBytes: romBytes);
+
+  Future<AudioChunk> runSamples({required int samples});
+
+  Future<Uint8List> saveState();
+
   Future<void> setButtons({required int pressed});
 
+  Future<void> setRewindSeconds({required int seconds});
+
+  Future<void> setSampleRate({required int sampleRate});
+
   Future<Uint8List> tick();
 }
+
+class AudioChunk {
+  final Int16List samples;
+  final Uint8List? frame;
+
+  const AudioChunk({required this.samples, this.frame});
+
+  @override
+  int get hashCode => samples.hashCode ^ frame.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is AudioChunk &&
+          runtimeType == other.runtimeType &&
+          samples == other.samples &&
+          frame == other.frame;
+}
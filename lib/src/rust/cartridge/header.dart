@@ -0,0 +1,89 @@
+// This file is automatically generated, so please do not edit it.
+// @generated by `flutter_rust_bridge`@ 2.11.1.
+
+// ignore_for_file: invalid_use_of_internal_member, unused_import, unnecessary_import
+
+import '../frb_generated.dart';
+import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+
+class CartridgeHeader {
+  final String title;
+  final String manufacturerCode;
+  final CgbSupport cgbSupport;
+  final bool sgbSupport;
+  final int oldLicenseeCode;
+  final String newLicenseeCode;
+  final int cartridgeType;
+  final String mapper;
+  final int romSize;
+  final int ramSize;
+  final int version;
+  final int headerChecksum;
+  final bool headerChecksumValid;
+  final int globalChecksum;
+  final bool globalChecksumValid;
+
+  const CartridgeHeader({
+    required this.title,
+    required this.manufacturerCode,
+    required this.cgbSupport,
+    required this.sgbSupport,
+    required this.oldLicenseeCode,
+    required this.newLicenseeCode,
+    required this.cartridgeType,
+    required this.mapper,
+    required this.romSize,
+    required this.ramSize,
+    required this.version,
+    required this.headerChecksum,
+    required this.headerChecksumValid,
+    required this.globalChecksum,
+    required this.globalChecksumValid,
+  });
+
+  @override
+  int get hashCode =>
+      title.hashCode ^
+      manufacturerCode.hashCode ^
+      cgbSupport.hashCode ^
+      sgbSupport.hashCode ^
+      oldLicenseeCode.hashCode ^
+      newLicenseeCode.hashCode ^
+      cartridgeType.hashCode ^
+      mapper.hashCode ^
+      romSize.hashCode ^
+      ramSize.hashCode ^
+      version.hashCode ^
+      headerChecksum.hashCode ^
+      headerChecksumValid.hashCode ^
+      globalChecksum.hashCode ^
+      globalChecksumValid.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is CartridgeHeader &&
+          runtimeType == other.runtimeType &&
+          title == other.title &&
+          manufacturerCode == other.manufacturerCode &&
+          cgbSupport == other.cgbSupport &&
+          sgbSupport == other.sgbSupport &&
+          oldLicenseeCode == other.oldLicenseeCode &&
+          newLicenseeCode == other.newLicenseeCode &&
+          cartridgeType == other.cartridgeType &&
+          mapper == other.mapper &&
+          romSize == other.romSize &&
+          ramSize == other.ramSize &&
+          version == other.version &&
+          headerChecksum == other.headerChecksum &&
+          headerChecksumValid == other.headerChecksumValid &&
+          globalChecksum == other.globalChecksum &&
+          globalChecksumValid == other.globalChecksumValid;
+}
+
+enum CgbSupport {
+  dmgOnly,
+  enhanced,
+  cgbOnly,
+  ;
+}
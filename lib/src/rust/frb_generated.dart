@@ -4,12 +4,15 @@
 // ignore_for_file: unused_import, unused_element, unnecessary_import, duplicate_ignore, invalid_use_of_internal_member, annotate_overrides, non_constant_identifier_names, curly_braces_in_flow_control_structures, prefer_const_literals_to_create_immutables, unused_field
 
 import 'api/proxy.dart';
+import 'cartridge.dart';
+import 'cartridge/header.dart';
 import 'dart:async';
 import 'dart:convert';
 import 'frb_generated.dart';
 import 'frb_generated.io.dart'
     if (dart.library.js_interop) 'frb_generated.web.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
+import 'savestate.dart';
 
 /// Main entrypoint of the Rust API
 class RustLib extends BaseEntrypoint<RustLibApi, RustLibApiImpl, RustLibWire> {
@@ -64,7 +67,7 @@ class RustLib extends BaseEntrypoint<RustLibApi, RustLibApiImpl, RustLibWire> {
   String get codegenVersion => '2.11.1';
 
   @override
-  int get rustContentHash => -49333787;
+  int get rustContentHash => 44088173;
 
   static const kDefaultExternalLibraryLoaderConfig =
       ExternalLibraryLoaderConfig(
@@ -75,13 +78,68 @@ class RustLib extends BaseEntrypoint<RustLibApi, RustLibApiImpl, RustLibWire> {
 }
 
 abstract class RustLibApi extends BaseApi {
+  Future<Int16List> crateApiProxyDancyProxyGetAudioSamples({
+    required DancyProxy that,
+  });
+
+  Future<bool> crateApiProxyDancyProxyGetRumble({required DancyProxy that});
+
+  Future<Uint8List> crateApiProxyDancyProxyGetSaveData({
+    required DancyProxy that,
+  });
+
+  Future<bool> crateApiProxyDancyProxyIsSaveDirty({required DancyProxy that});
+
+  Future<void> crateApiProxyDancyProxyLoadState({
+    required DancyProxy that,
+    required List<int> data,
+  });
+
   Future<DancyProxy> crateApiProxyDancyProxyNew({required List<int> romBytes});
 
+  Future<DancyProxy> crateApiProxyDancyProxyNewWithBootRom({
+    required List<int> romBytes,
+    required List<int> saveData,
+    required List<int> bootRom,
+  });
+
+  Future<DancyProxy> crateApiProxyDancyProxyNewWithSaveData({
+    required List<int> romBytes,
+    required List<int> saveData,
+  });
+
+  Future<Uint8List> crateApiProxyDancyProxyRewindFrame({
+    required DancyProxy that,
+  });
+
+  Future<CartridgeHeader> crateApiProxyDancyProxyRomInfo({
+    required List<int> romBytes,
+  });
+
+  Future<AudioChunk> crateApiProxyDancyProxyRunSamples({
+    required DancyProxy that,
+    required int samples,
+  });
+
+  Future<Uint8List> crateApiProxyDancyProxySaveState({
+    required DancyProxy that,
+  });
+
   Future<void> crateApiProxyDancyProxySetButtons({
     required DancyProxy that,
     required int pressed,
   });
 
+  Future<void> crateApiProxyDancyProxySetRewindSeconds({
+    required DancyProxy that,
+    required int seconds,
+  });
+
+  Future<void> crateApiProxyDancyProxySetSampleRate({
+    required DancyProxy that,
+    required int sampleRate,
+  });
+
   Future<Uint8List> crateApiProxyDancyProxyTick({required DancyProxy that});
 
   RustArcIncrementStrongCountFnType
@@ -93,47 +151,518 @@ abstract class RustLibApi extends BaseApi {
   CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_DancyProxyPtr;
 }
 
-class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
-  RustLibApiImpl({
-    required super.handler,
-    required super.wire,
-    required super.generalizedFrbRustBinding,
-    required super.portManager,
-  });
+class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
+  RustLibApiImpl({
+    required super.handler,
+    required super.wire,
+    required super.generalizedFrbRustBinding,
+    required super.portManager,
+  });
+
+  @override
+  Future<Int16List> crateApiProxyDancyProxyGetAudioSamples({
+    required DancyProxy that,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 1,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_list_prim_i_16_strict,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxyGetAudioSamplesConstMeta,
+        argValues: [that],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyGetAudioSamplesConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_get_audio_samples",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<bool> crateApiProxyDancyProxyGetRumble({required DancyProxy that}) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 2,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_bool,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxyGetRumbleConstMeta,
+        argValues: [that],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyGetRumbleConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_get_rumble",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<Uint8List> crateApiProxyDancyProxyGetSaveData({
+    required DancyProxy that,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 3,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_list_prim_u_8_strict,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxyGetSaveDataConstMeta,
+        argValues: [that],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyGetSaveDataConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_get_save_data",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<bool> crateApiProxyDancyProxyIsSaveDirty({required DancyProxy that}) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 4,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_bool,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxyIsSaveDirtyConstMeta,
+        argValues: [that],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyIsSaveDirtyConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_is_save_dirty",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<void> crateApiProxyDancyProxyLoadState({
+    required DancyProxy that,
+    required List<int> data,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          sse_encode_list_prim_u_8_loose(data, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 5,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: sse_decode_state_error,
+        ),
+        constMeta: kCrateApiProxyDancyProxyLoadStateConstMeta,
+        argValues: [that, data],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyLoadStateConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_load_state",
+        argNames: ["that", "data"],
+      );
+
+  @override
+  Future<DancyProxy> crateApiProxyDancyProxyNew({required List<int> romBytes}) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_list_prim_u_8_loose(romBytes, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 6,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData:
+              sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy,
+          decodeErrorData: sse_decode_cartridge_error,
+        ),
+        constMeta: kCrateApiProxyDancyProxyNewConstMeta,
+        argValues: [romBytes],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyNewConstMeta =>
+      const TaskConstMeta(debugName: "DancyProxy_new", argNames: ["romBytes"]);
+
+  @override
+  Future<DancyProxy> crateApiProxyDancyProxyNewWithBootRom({
+    required List<int> romBytes,
+    required List<int> saveData,
+    required List<int> bootRom,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_list_prim_u_8_loose(romBytes, serializer);
+          sse_encode_list_prim_u_8_loose(saveData, serializer);
+          sse_encode_list_prim_u_8_loose(bootRom, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 7,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData:
+              sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy,
+          decodeErrorData: sse_decode_cartridge_error,
+        ),
+        constMeta: kCrateApiProxyDancyProxyNewWithBootRomConstMeta,
+        argValues: [romBytes, saveData, bootRom],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyNewWithBootRomConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_new_with_boot_rom",
+        argNames: ["romBytes", "saveData", "bootRom"],
+      );
+
+  @override
+  Future<DancyProxy> crateApiProxyDancyProxyNewWithSaveData({
+    required List<int> romBytes,
+    required List<int> saveData,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_list_prim_u_8_loose(romBytes, serializer);
+          sse_encode_list_prim_u_8_loose(saveData, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 8,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData:
+              sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy,
+          decodeErrorData: sse_decode_cartridge_error,
+        ),
+        constMeta: kCrateApiProxyDancyProxyNewWithSaveDataConstMeta,
+        argValues: [romBytes, saveData],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyNewWithSaveDataConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_new_with_save_data",
+        argNames: ["romBytes", "saveData"],
+      );
+
+  @override
+  Future<Uint8List> crateApiProxyDancyProxyRewindFrame({
+    required DancyProxy that,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 9,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_list_prim_u_8_strict,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxyRewindFrameConstMeta,
+        argValues: [that],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyRewindFrameConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_rewind_frame",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<CartridgeHeader> crateApiProxyDancyProxyRomInfo({
+    required List<int> romBytes,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_list_prim_u_8_loose(romBytes, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 10,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_cartridge_header,
+          decodeErrorData: sse_decode_cartridge_error,
+        ),
+        constMeta: kCrateApiProxyDancyProxyRomInfoConstMeta,
+        argValues: [romBytes],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyRomInfoConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_rom_info",
+        argNames: ["romBytes"],
+      );
+
+  @override
+  Future<AudioChunk> crateApiProxyDancyProxyRunSamples({
+    required DancyProxy that,
+    required int samples,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          sse_encode_u_32(samples, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 11,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_audio_chunk,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxyRunSamplesConstMeta,
+        argValues: [that, samples],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxyRunSamplesConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_run_samples",
+        argNames: ["that", "samples"],
+      );
+
+  @override
+  Future<Uint8List> crateApiProxyDancyProxySaveState({
+    required DancyProxy that,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 12,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_list_prim_u_8_strict,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxySaveStateConstMeta,
+        argValues: [that],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxySaveStateConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_save_state",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<void> crateApiProxyDancyProxySetButtons({
+    required DancyProxy that,
+    required int pressed,
+  }) {
+    return handler.executeNormal(
+      NormalTask(
+        callFfi: (port_) {
+          final serializer = SseSerializer(generalizedFrbRustBinding);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          sse_encode_u_8(pressed, serializer);
+          pdeCallFfi(
+            generalizedFrbRustBinding,
+            serializer,
+            funcId: 13,
+            port: port_,
+          );
+        },
+        codec: SseCodec(
+          decodeSuccessData: sse_decode_unit,
+          decodeErrorData: null,
+        ),
+        constMeta: kCrateApiProxyDancyProxySetButtonsConstMeta,
+        argValues: [that, pressed],
+        apiImpl: this,
+      ),
+    );
+  }
+
+  TaskConstMeta get kCrateApiProxyDancyProxySetButtonsConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_set_buttons",
+        argNames: ["that", "pressed"],
+      );
 
   @override
-  Future<DancyProxy> crateApiProxyDancyProxyNew({required List<int> romBytes}) {
+  Future<void> crateApiProxyDancyProxySetRewindSeconds({
+    required DancyProxy that,
+    required int seconds,
+  }) {
     return handler.executeNormal(
       NormalTask(
         callFfi: (port_) {
           final serializer = SseSerializer(generalizedFrbRustBinding);
-          sse_encode_list_prim_u_8_loose(romBytes, serializer);
+          sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
+            that,
+            serializer,
+          );
+          sse_encode_u_32(seconds, serializer);
           pdeCallFfi(
             generalizedFrbRustBinding,
             serializer,
-            funcId: 1,
+            funcId: 14,
             port: port_,
           );
         },
         codec: SseCodec(
-          decodeSuccessData:
-              sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy,
+          decodeSuccessData: sse_decode_unit,
           decodeErrorData: null,
         ),
-        constMeta: kCrateApiProxyDancyProxyNewConstMeta,
-        argValues: [romBytes],
+        constMeta: kCrateApiProxyDancyProxySetRewindSecondsConstMeta,
+        argValues: [that, seconds],
         apiImpl: this,
       ),
     );
   }
 
-  TaskConstMeta get kCrateApiProxyDancyProxyNewConstMeta =>
-      const TaskConstMeta(debugName: "DancyProxy_new", argNames: ["romBytes"]);
+  TaskConstMeta get kCrateApiProxyDancyProxySetRewindSecondsConstMeta =>
+      const TaskConstMeta(
+        debugName: "DancyProxy_set_rewind_seconds",
+        argNames: ["that", "seconds"],
+      );
 
   @override
-  Future<void> crateApiProxyDancyProxySetButtons({
+  Future<void> crateApiProxyDancyProxySetSampleRate({
     required DancyProxy that,
-    required int pressed,
+    required int sampleRate,
   }) {
     return handler.executeNormal(
       NormalTask(
@@ -143,11 +672,11 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
             that,
             serializer,
           );
-          sse_encode_u_8(pressed, serializer);
+          sse_encode_u_32(sampleRate, serializer);
           pdeCallFfi(
             generalizedFrbRustBinding,
             serializer,
-            funcId: 2,
+            funcId: 15,
             port: port_,
           );
         },
@@ -155,17 +684,17 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
           decodeSuccessData: sse_decode_unit,
           decodeErrorData: null,
         ),
-        constMeta: kCrateApiProxyDancyProxySetButtonsConstMeta,
-        argValues: [that, pressed],
+        constMeta: kCrateApiProxyDancyProxySetSampleRateConstMeta,
+        argValues: [that, sampleRate],
         apiImpl: this,
       ),
     );
   }
 
-  TaskConstMeta get kCrateApiProxyDancyProxySetButtonsConstMeta =>
+  TaskConstMeta get kCrateApiProxyDancyProxySetSampleRateConstMeta =>
       const TaskConstMeta(
-        debugName: "DancyProxy_set_buttons",
-        argNames: ["that", "pressed"],
+        debugName: "DancyProxy_set_sample_rate",
+        argNames: ["that", "sampleRate"],
       );
 
   @override
@@ -181,7 +710,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
           pdeCallFfi(
             generalizedFrbRustBinding,
             serializer,
-            funcId: 3,
+            funcId: 16,
             port: port_,
           );
         },
@@ -234,6 +763,79 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return DancyProxyImpl.frbInternalDcoDecode(raw as List<dynamic>);
   }
 
+  @protected
+  String dco_decode_String(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as String;
+  }
+
+  @protected
+  AudioChunk dco_decode_audio_chunk(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 2)
+      throw Exception('unexpected arr length: expect 2 but see ${arr.length}');
+    return AudioChunk(
+      samples: dco_decode_list_prim_i_16_strict(arr[0]),
+      frame: dco_decode_opt_list_prim_u_8_strict(arr[1]),
+    );
+  }
+
+  @protected
+  bool dco_decode_bool(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as bool;
+  }
+
+  @protected
+  CartridgeError dco_decode_cartridge_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return CartridgeError.values[raw as int];
+  }
+
+  @protected
+  CartridgeHeader dco_decode_cartridge_header(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 15)
+      throw Exception('unexpected arr length: expect 15 but see ${arr.length}');
+    return CartridgeHeader(
+      title: dco_decode_String(arr[0]),
+      manufacturerCode: dco_decode_String(arr[1]),
+      cgbSupport: dco_decode_cgb_support(arr[2]),
+      sgbSupport: dco_decode_bool(arr[3]),
+      oldLicenseeCode: dco_decode_u_8(arr[4]),
+      newLicenseeCode: dco_decode_String(arr[5]),
+      cartridgeType: dco_decode_u_8(arr[6]),
+      mapper: dco_decode_String(arr[7]),
+      romSize: dco_decode_u_32(arr[8]),
+      ramSize: dco_decode_u_32(arr[9]),
+      version: dco_decode_u_8(arr[10]),
+      headerChecksum: dco_decode_u_8(arr[11]),
+      headerChecksumValid: dco_decode_bool(arr[12]),
+      globalChecksum: dco_decode_u_16(arr[13]),
+      globalChecksumValid: dco_decode_bool(arr[14]),
+    );
+  }
+
+  @protected
+  CgbSupport dco_decode_cgb_support(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return CgbSupport.values[raw as int];
+  }
+
+  @protected
+  int dco_decode_i_16(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as int;
+  }
+
+  @protected
+  Int16List dco_decode_list_prim_i_16_strict(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as Int16List;
+  }
+
   @protected
   List<int> dco_decode_list_prim_u_8_loose(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -246,6 +848,30 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return raw as Uint8List;
   }
 
+  @protected
+  Uint8List? dco_decode_opt_list_prim_u_8_strict(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_list_prim_u_8_strict(raw);
+  }
+
+  @protected
+  StateError dco_decode_state_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return StateError.values[raw as int];
+  }
+
+  @protected
+  int dco_decode_u_16(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as int;
+  }
+
+  @protected
+  int dco_decode_u_32(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as int;
+  }
+
   @protected
   int dco_decode_u_8(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -300,6 +926,91 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     );
   }
 
+  @protected
+  String sse_decode_String(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_list_prim_u_8_strict(deserializer);
+    return utf8.decoder.convert(inner);
+  }
+
+  @protected
+  AudioChunk sse_decode_audio_chunk(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_samples = sse_decode_list_prim_i_16_strict(deserializer);
+    var var_frame = sse_decode_opt_list_prim_u_8_strict(deserializer);
+    return AudioChunk(samples: var_samples, frame: var_frame);
+  }
+
+  @protected
+  bool sse_decode_bool(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getUint8() != 0;
+  }
+
+  @protected
+  CartridgeError sse_decode_cartridge_error(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_i_32(deserializer);
+    return CartridgeError.values[inner];
+  }
+
+  @protected
+  CartridgeHeader sse_decode_cartridge_header(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_title = sse_decode_String(deserializer);
+    var var_manufacturerCode = sse_decode_String(deserializer);
+    var var_cgbSupport = sse_decode_cgb_support(deserializer);
+    var var_sgbSupport = sse_decode_bool(deserializer);
+    var var_oldLicenseeCode = sse_decode_u_8(deserializer);
+    var var_newLicenseeCode = sse_decode_String(deserializer);
+    var var_cartridgeType = sse_decode_u_8(deserializer);
+    var var_mapper = sse_decode_String(deserializer);
+    var var_romSize = sse_decode_u_32(deserializer);
+    var var_ramSize = sse_decode_u_32(deserializer);
+    var var_version = sse_decode_u_8(deserializer);
+    var var_headerChecksum = sse_decode_u_8(deserializer);
+    var var_headerChecksumValid = sse_decode_bool(deserializer);
+    var var_globalChecksum = sse_decode_u_16(deserializer);
+    var var_globalChecksumValid = sse_decode_bool(deserializer);
+    return CartridgeHeader(
+      title: var_title,
+      manufacturerCode: var_manufacturerCode,
+      cgbSupport: var_cgbSupport,
+      sgbSupport: var_sgbSupport,
+      oldLicenseeCode: var_oldLicenseeCode,
+      newLicenseeCode: var_newLicenseeCode,
+      cartridgeType: var_cartridgeType,
+      mapper: var_mapper,
+      romSize: var_romSize,
+      ramSize: var_ramSize,
+      version: var_version,
+      headerChecksum: var_headerChecksum,
+      headerChecksumValid: var_headerChecksumValid,
+      globalChecksum: var_globalChecksum,
+      globalChecksumValid: var_globalChecksumValid,
+    );
+  }
+
+  @protected
+  CgbSupport sse_decode_cgb_support(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_i_32(deserializer);
+    return CgbSupport.values[inner];
+  }
+
+  @protected
+  int sse_decode_i_16(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getInt16();
+  }
+
+  @protected
+  Int16List sse_decode_list_prim_i_16_strict(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var len_ = sse_decode_i_32(deserializer);
+    return deserializer.buffer.getInt16List(len_);
+  }
+
   @protected
   List<int> sse_decode_list_prim_u_8_loose(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -314,6 +1025,36 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return deserializer.buffer.getUint8List(len_);
   }
 
+  @protected
+  Uint8List? sse_decode_opt_list_prim_u_8_strict(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_list_prim_u_8_strict(deserializer));
+    } else {
+      return null;
+    }
+  }
+
+  @protected
+  StateError sse_decode_state_error(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_i_32(deserializer);
+    return StateError.values[inner];
+  }
+
+  @protected
+  int sse_decode_u_16(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getUint16();
+  }
+
+  @protected
+  int sse_decode_u_32(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getUint32();
+  }
+
   @protected
   int sse_decode_u_8(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -337,12 +1078,6 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return deserializer.buffer.getInt32();
   }
 
-  @protected
-  bool sse_decode_bool(SseDeserializer deserializer) {
-    // Codec=Sse (Serialization based), see doc to use other codecs
-    return deserializer.buffer.getUint8() != 0;
-  }
-
   @protected
   void
   sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedRustAutoOpaqueInnerDancyProxy(
@@ -382,6 +1117,79 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     );
   }
 
+  @protected
+  void sse_encode_String(String self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_list_prim_u_8_strict(utf8.encoder.convert(self), serializer);
+  }
+
+  @protected
+  void sse_encode_audio_chunk(AudioChunk self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_list_prim_i_16_strict(self.samples, serializer);
+    sse_encode_opt_list_prim_u_8_strict(self.frame, serializer);
+  }
+
+  @protected
+  void sse_encode_bool(bool self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    serializer.buffer.putUint8(self ? 1 : 0);
+  }
+
+  @protected
+  void sse_encode_cartridge_error(
+    CartridgeError self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.index, serializer);
+  }
+
+  @protected
+  void sse_encode_cartridge_header(
+    CartridgeHeader self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.title, serializer);
+    sse_encode_String(self.manufacturerCode, serializer);
+    sse_encode_cgb_support(self.cgbSupport, serializer);
+    sse_encode_bool(self.sgbSupport, serializer);
+    sse_encode_u_8(self.oldLicenseeCode, serializer);
+    sse_encode_String(self.newLicenseeCode, serializer);
+    sse_encode_u_8(self.cartridgeType, serializer);
+    sse_encode_String(self.mapper, serializer);
+    sse_encode_u_32(self.romSize, serializer);
+    sse_encode_u_32(self.ramSize, serializer);
+    sse_encode_u_8(self.version, serializer);
+    sse_encode_u_8(self.headerChecksum, serializer);
+    sse_encode_bool(self.headerChecksumValid, serializer);
+    sse_encode_u_16(self.globalChecksum, serializer);
+    sse_encode_bool(self.globalChecksumValid, serializer);
+  }
+
+  @protected
+  void sse_encode_cgb_support(CgbSupport self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.index, serializer);
+  }
+
+  @protected
+  void sse_encode_i_16(int self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    serializer.buffer.putInt16(self);
+  }
+
+  @protected
+  void sse_encode_list_prim_i_16_strict(
+    Int16List self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    serializer.buffer.putInt16List(self);
+  }
+
   @protected
   void sse_encode_list_prim_u_8_loose(
     List<int> self,
@@ -404,6 +1212,37 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     serializer.buffer.putUint8List(self);
   }
 
+  @protected
+  void sse_encode_opt_list_prim_u_8_strict(
+    Uint8List? self,
+    SseSerializer serializer,
+  ) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_list_prim_u_8_strict(self, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_state_error(StateError self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.index, serializer);
+  }
+
+  @protected
+  void sse_encode_u_16(int self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    serializer.buffer.putUint16(self);
+  }
+
+  @protected
+  void sse_encode_u_32(int self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    serializer.buffer.putUint32(self);
+  }
+
   @protected
   void sse_encode_u_8(int self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -426,12 +1265,6 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     // Codec=Sse (Serialization based), see doc to use other codecs
     serializer.buffer.putInt32(self);
   }
-
-  @protected
-  void sse_encode_bool(bool self, SseSerializer serializer) {
-    // Codec=Sse (Serialization based), see doc to use other codecs
-    serializer.buffer.putUint8(self ? 1 : 0);
-  }
 }
 
 @sealed
@@ -453,9 +1286,39 @@ class DancyProxyImpl extends RustOpaque implements DancyProxy {
         RustLib.instance.api.rust_arc_decrement_strong_count_DancyProxyPtr,
   );
 
+  Future<Int16List> getAudioSamples() =>
+      RustLib.instance.api.crateApiProxyDancyProxyGetAudioSamples(that: this);
+
+  Future<bool> getRumble() =>
+      RustLib.instance.api.crateApiProxyDancyProxyGetRumble(that: this);
+
+  Future<Uint8List> getSaveData() =>
+      RustLib.instance.api.crateApiProxyDancyProxyGetSaveData(that: this);
+
+  Future<bool> isSaveDirty() =>
+      RustLib.instance.api.crateApiProxyDancyProxyIsSaveDirty(that: this);
+
+  Future<void> loadState({required List<int> data}) => RustLib.instance.api
+      .crateApiProxyDancyProxyLoadState(that: this, data: data);
+
+  Future<Uint8List> rewindFrame() =>
+      RustLib.instance.api.crateApiProxyDancyProxyRewindFrame(that: this);
+
+  Future<AudioChunk> runSamples({required int samples}) => RustLib.instance.api
+      .crateApiProxyDancyProxyRunSamples(that: this, samples: samples);
+
+  Future<Uint8List> saveState() =>
+      RustLib.instance.api.crateApiProxyDancyProxySaveState(that: this);
+
   Future<void> setButtons({required int pressed}) => RustLib.instance.api
       .crateApiProxyDancyProxySetButtons(that: this, pressed: pressed);
 
+  Future<void> setRewindSeconds({required int seconds}) => RustLib.instance.api
+      .crateApiProxyDancyProxySetRewindSeconds(that: this, seconds: seconds);
+
+  Future<void> setSampleRate({required int sampleRate}) => RustLib.instance.api
+      .crateApiProxyDancyProxySetSampleRate(that: this, sampleRate: sampleRate);
+
   Future<Uint8List> tick() =>
       RustLib.instance.api.crateApiProxyDancyProxyTick(that: this);
 }
@@ -1,5 +1,7 @@
 use std::ops::Deref;
 use crate::DancyHandle;
+use crate::cartridge::{CartridgeError, CartridgeHeader};
+use crate::savestate::StateError;
 use std::sync::mpsc::{channel, Sender, Receiver};
 use std::thread;
 use flutter_rust_bridge::frb;
@@ -7,22 +9,88 @@ use parking_lot::Mutex;
 
 enum EmuCommand {
     Tick,
+    RunSamples(u32),
     UpdateButtons(u8),
+    GetAudioSamples,
+    SetSampleRate(u32),
+    GetRumble,
+    GetSaveData,
+    IsSaveDirty,
+    SaveState,
+    LoadState(Vec<u8>),
+    RewindFrame,
+    SetRewindSeconds(u32),
+}
+
+// What run_samples produced. `frame` is the screen if a frame was finished along the way.
+pub struct AudioChunk {
+    pub samples: Vec<i16>,
+    pub frame: Option<Vec<u8>>,
 }
 
 #[frb(opaque)]
 pub struct DancyProxy {
     tx: Sender<EmuCommand>,
     frame_rx: Mutex<Receiver<Vec<u8>>>,
+    chunk_rx: Mutex<Receiver<AudioChunk>>,
+    audio_rx: Mutex<Receiver<Vec<i16>>>,
+    rumble_rx: Mutex<Receiver<bool>>,
+    save_rx: Mutex<Receiver<Vec<u8>>>,
+    save_dirty_rx: Mutex<Receiver<bool>>,
+    state_rx: Mutex<Receiver<Vec<u8>>>,
+    load_state_rx: Mutex<Receiver<Result<(), StateError>>>,
+    rewind_rx: Mutex<Receiver<Vec<u8>>>,
 }
 
 impl DancyProxy {
-    pub fn new(rom_bytes: Vec<u8>) -> DancyProxy {
+    pub fn new(rom_bytes: Vec<u8>) -> Result<DancyProxy, CartridgeError> {
+        Self::new_with_save_data(rom_bytes, Vec::new())
+    }
+
+    // Start with the contents of a .sav file
+    pub fn new_with_save_data(rom_bytes: Vec<u8>, save_data: Vec<u8>) -> Result<DancyProxy, CartridgeError> {
+        Self::spawn(rom_bytes, save_data, None)
+    }
+
+    // Run a DMG or CGB boot ROM image before the game, save_data may be empty
+    pub fn new_with_boot_rom(
+        rom_bytes: Vec<u8>,
+        save_data: Vec<u8>,
+        boot_rom: Vec<u8>,
+    ) -> Result<DancyProxy, CartridgeError> {
+        Self::spawn(rom_bytes, save_data, Some(boot_rom))
+    }
+
+    fn spawn(rom_bytes: Vec<u8>, save_data: Vec<u8>, boot_rom: Option<Vec<u8>>) -> Result<DancyProxy, CartridgeError> {
+        let (init_tx, init_rx) = channel::<Result<(), CartridgeError>>();
         let (cmd_tx, cmd_rx) = channel::<EmuCommand>();
         let (frame_tx, frame_rx) = channel::<Vec<u8>>();
+        let (chunk_tx, chunk_rx) = channel::<AudioChunk>();
+        let (audio_tx, audio_rx) = channel::<Vec<i16>>();
+        let (rumble_tx, rumble_rx) = channel::<bool>();
+        let (save_tx, save_rx) = channel::<Vec<u8>>();
+        let (save_dirty_tx, save_dirty_rx) = channel::<bool>();
+        let (state_tx, state_rx) = channel::<Vec<u8>>();
+        let (load_state_tx, load_state_rx) = channel::<Result<(), StateError>>();
+        let (rewind_tx, rewind_rx) = channel::<Vec<u8>>();
 
         thread::spawn(move || {
-            let mut emulator = DancyHandle::new(rom_bytes);
+            // The handle isn't Send, so it's built here and only the outcome goes back
+            let emulator = match boot_rom {
+                Some(boot_rom) => DancyHandle::with_boot_rom(rom_bytes, boot_rom),
+                None => DancyHandle::new(rom_bytes),
+            };
+            let mut emulator = match emulator {
+                Ok(emulator) => {
+                    emulator.load_save_data(&save_data);
+                    let _ = init_tx.send(Ok(()));
+                    emulator
+                }
+                Err(e) => {
+                    let _ = init_tx.send(Err(e));
+                    return;
+                }
+            };
 
             while let Ok(cmd) = cmd_rx.recv() {
                 match cmd {
@@ -31,17 +99,70 @@ impl DancyProxy {
                         let pixels = emulator.get_graphics();
                         let _ = frame_tx.send(pixels);
                     }
+                    EmuCommand::RunSamples(samples) => {
+                        let frame_done = emulator.run_samples(samples);
+                        let _ = chunk_tx.send(AudioChunk {
+                            samples: emulator.get_audio_samples(),
+                            frame: frame_done.then(|| emulator.get_graphics()),
+                        });
+                    }
                     EmuCommand::UpdateButtons(state) => {
                         emulator.update_buttons(state);
                     }
+                    EmuCommand::GetAudioSamples => {
+                        let samples = emulator.get_audio_samples();
+                        let _ = audio_tx.send(samples);
+                    }
+                    EmuCommand::SetSampleRate(sample_rate) => {
+                        emulator.set_sample_rate(sample_rate);
+                    }
+                    EmuCommand::GetRumble => {
+                        let _ = rumble_tx.send(emulator.get_rumble());
+                    }
+                    EmuCommand::GetSaveData => {
+                        let _ = save_tx.send(emulator.get_save_data());
+                    }
+                    EmuCommand::IsSaveDirty => {
+                        let _ = save_dirty_tx.send(emulator.is_save_dirty());
+                    }
+                    EmuCommand::SaveState => {
+                        let _ = state_tx.send(emulator.save_state());
+                    }
+                    EmuCommand::LoadState(data) => {
+                        let _ = load_state_tx.send(emulator.load_state(&data));
+                    }
+                    EmuCommand::RewindFrame => {
+                        let _ = rewind_tx.send(emulator.rewind_frame());
+                    }
+                    EmuCommand::SetRewindSeconds(seconds) => {
+                        emulator.set_rewind_seconds(seconds);
+                    }
                 }
             }
         });
 
-        DancyProxy {
+        init_rx.recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive load result from emulator core. Error: {}", e);
+            Err(CartridgeError::Unavailable)
+        })?;
+
+        Ok(DancyProxy {
             tx: cmd_tx,
             frame_rx: Mutex::new(frame_rx),
-        }
+            chunk_rx: Mutex::new(chunk_rx),
+            audio_rx: Mutex::new(audio_rx),
+            rumble_rx: Mutex::new(rumble_rx),
+            save_rx: Mutex::new(save_rx),
+            save_dirty_rx: Mutex::new(save_dirty_rx),
+            state_rx: Mutex::new(state_rx),
+            load_state_rx: Mutex::new(load_state_rx),
+            rewind_rx: Mutex::new(rewind_rx),
+        })
+    }
+
+    // Header metadata without starting an emulator thread, for ROM lists
+    pub fn rom_info(rom_bytes: Vec<u8>) -> Result<CartridgeHeader, CartridgeError> {
+        CartridgeHeader::parse(&rom_bytes)
     }
 
     pub fn tick(&self) -> Vec<u8> {
@@ -55,7 +176,127 @@ impl DancyProxy {
         })
     }
 
+    // Instead of tick, for frontends paced by the audio device: run until `samples` more stereo
+    // samples are mixed and return them, along with the screen if a frame was finished
+    pub fn run_samples(&self, samples: u32) -> AudioChunk {
+        self.tx.send(EmuCommand::RunSamples(samples)).unwrap_or_else(| e |
+            eprintln!("Cannot run samples. Error: {}", e)
+        );
+        self.chunk_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive samples from emulator core. Error: {}", e);
+            AudioChunk { samples: vec!(), frame: None }
+        })
+    }
+
     pub fn set_buttons(&self, pressed: u8) {
         let _ = self.tx.send(EmuCommand::UpdateButtons(pressed));
     }
-}
\ No newline at end of file
+
+    // Interleaved stereo samples (L, R, L, R...) produced since the last call
+    pub fn get_audio_samples(&self) -> Vec<i16> {
+        self.tx.send(EmuCommand::GetAudioSamples).unwrap_or_else(| e |
+            eprintln!("Cannot request audio samples. Error: {}", e)
+        );
+        self.audio_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive audio samples from emulator core. Error: {}", e);
+            vec!()
+        })
+    }
+
+    // Hz, clamped to 8000-192000
+    pub fn set_sample_rate(&self, sample_rate: u32) {
+        let _ = self.tx.send(EmuCommand::SetSampleRate(sample_rate));
+    }
+
+    // Rumble motor state, poll after tick to drive haptics
+    pub fn get_rumble(&self) -> bool {
+        self.tx.send(EmuCommand::GetRumble).unwrap_or_else(| e |
+            eprintln!("Cannot request rumble state. Error: {}", e)
+        );
+        self.rumble_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive rumble state from emulator core. Error: {}", e);
+            false
+        })
+    }
+
+    // Bytes for the .sav file, empty if the cartridge has no battery. Clears the dirty flag.
+    pub fn get_save_data(&self) -> Vec<u8> {
+        self.tx.send(EmuCommand::GetSaveData).unwrap_or_else(| e |
+            eprintln!("Cannot request save data. Error: {}", e)
+        );
+        self.save_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive save data from emulator core. Error: {}", e);
+            vec!()
+        })
+    }
+
+    // True once the game writes save RAM, flush get_save_data to disk then
+    pub fn is_save_dirty(&self) -> bool {
+        self.tx.send(EmuCommand::IsSaveDirty).unwrap_or_else(| e |
+            eprintln!("Cannot request save dirty flag. Error: {}", e)
+        );
+        self.save_dirty_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive save dirty flag from emulator core. Error: {}", e);
+            false
+        })
+    }
+
+    // Snapshot of the whole machine, load it back with load_state
+    pub fn save_state(&self) -> Vec<u8> {
+        self.tx.send(EmuCommand::SaveState).unwrap_or_else(| e |
+            eprintln!("Cannot request save state. Error: {}", e)
+        );
+        self.state_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive save state from emulator core. Error: {}", e);
+            vec!()
+        })
+    }
+
+    // Fails without changing anything if the state is corrupt or from another ROM
+    pub fn load_state(&self, data: Vec<u8>) -> Result<(), StateError> {
+        self.tx.send(EmuCommand::LoadState(data)).unwrap_or_else(| e |
+            eprintln!("Cannot load save state. Error: {}", e)
+        );
+        self.load_state_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive load result from emulator core. Error: {}", e);
+            Err(StateError::Unavailable)
+        })
+    }
+
+    // Call instead of tick while rewind is held, steps one frame back and returns it
+    pub fn rewind_frame(&self) -> Vec<u8> {
+        self.tx.send(EmuCommand::RewindFrame).unwrap_or_else(| e |
+            eprintln!("Cannot rewind frame. Error: {}", e)
+        );
+        self.rewind_rx.lock().deref().recv().unwrap_or_else(|e| {
+            eprintln!("Cannot receive rewound frame from emulator core. Error: {}", e);
+            vec!()
+        })
+    }
+
+    // Off until this is called, 0 turns it off again and saves the per-frame snapshot. Drops the current history.
+    pub fn set_rewind_seconds(&self, seconds: u32) {
+        let _ = self.tx.send(EmuCommand::SetRewindSeconds(seconds));
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_samples() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let proxy = DancyProxy::new(rom).unwrap();
+        // 800 samples at 48 kHz is most of a frame, so one comes out every other call or so
+        let mut frames = 0;
+        for _ in 0..4 {
+            let chunk = proxy.run_samples(800);
+            assert!(chunk.samples.len() >= 800 * 2);
+            if let Some(frame) = chunk.frame {
+                assert_eq!(frame.len(), 160 * 144 * 4);
+                frames += 1;
+            }
+        }
+        assert!((2..=4).contains(&frames), "{}", frames);
+    }
+}
@@ -0,0 +1,235 @@
+// Headless runner for test ROMs and regression checks, no Flutter needed
+use rust_lib_dancy::DancyHandle;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: dancy-cli <rom> [options]
+
+Options:
+  --frames <n>             Run at most n frames (default 3600)
+  --until-serial <text>    Stop once the serial output contains text
+  --until-memory <addr=v>  Stop once the byte at addr is v, both hex (e.g. A000=00)
+  --input <file>           Button script, lines of `<frame> <buttons...>`
+  --png <file>             Write the last frame as a PNG
+  --boot-rom <file>        Run a boot ROM first
+  --cpu-log <file>         Write the registers before every instruction, up to 2 million lines
+
+Serial output is written to stdout as it arrives. Exits with 1 if an --until
+condition wasn't met within the frame limit, 2 on bad arguments or ROMs.";
+
+const DEFAULT_FRAMES: u64 = 3600;
+const CPU_LOG_LINES: u64 = 2_000_000;
+
+struct Options {
+    rom: String,
+    frames: u64,
+    until_serial: Option<String>,
+    until_memory: Option<(u16, u8)>,
+    input: Option<String>,
+    png: Option<String>,
+    boot_rom: Option<String>,
+    cpu_log: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+// Ok(false) if an --until condition was given and never met
+fn run(options: &Options) -> Result<bool, String> {
+    let rom = read_file(&options.rom)?;
+    let handle = match &options.boot_rom {
+        Some(path) => DancyHandle::with_boot_rom(rom, read_file(path)?),
+        None => DancyHandle::new(rom),
+    };
+    let mut handle = handle.map_err(|e| format!("Cannot load {}: {}", options.rom, e))?;
+    if let Some(path) = &options.cpu_log {
+        handle
+            .log_cpu(path, CPU_LOG_LINES)
+            .map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    }
+
+    let script = match &options.input {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            parse_script(&text).map_err(|e| format!("{}: {}", path, e))?
+        }
+        None => BTreeMap::new(),
+    };
+
+    let has_condition = options.until_serial.is_some() || options.until_memory.is_some();
+    let mut serial = handle.take_serial_output(); // Start collecting
+    let mut stdout = std::io::stdout();
+    let mut met = false;
+    for frame in 0..options.frames {
+        if let Some(&pressed) = script.get(&frame) {
+            handle.update_buttons(pressed);
+        }
+        handle.run_frame();
+
+        let output = handle.take_serial_output();
+        if !output.is_empty() {
+            let _ = stdout.write_all(output.as_bytes());
+            let _ = stdout.flush();
+            serial.push_str(&output);
+        }
+
+        met = options
+            .until_serial
+            .as_ref()
+            .is_some_and(|text| serial.contains(text.as_str()))
+            || options
+                .until_memory
+                .is_some_and(|(addr, value)| handle.bus().debug_read(addr) == value);
+        if met {
+            break;
+        }
+    }
+    if !serial.is_empty() && !serial.ends_with('\n') {
+        println!();
+    }
+
+    if let Some(path) = &options.png {
+        write_png(path, &handle.get_graphics())?;
+    }
+    if has_condition && !met {
+        eprintln!("Condition not met after {} frames", options.frames);
+    }
+    Ok(met || !has_condition)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        rom: String::new(),
+        frames: DEFAULT_FRAMES,
+        until_serial: None,
+        until_memory: None,
+        input: None,
+        png: None,
+        boot_rom: None,
+        cpu_log: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+        match arg.as_str() {
+            "--frames" => options.frames = value()?.parse().map_err(|_| "--frames needs a number")?,
+            "--until-serial" => options.until_serial = Some(value()?),
+            "--until-memory" => options.until_memory = Some(parse_memory_condition(&value()?)?),
+            "--input" => options.input = Some(value()?),
+            "--png" => options.png = Some(value()?),
+            "--boot-rom" => options.boot_rom = Some(value()?),
+            "--cpu-log" => options.cpu_log = Some(value()?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if options.rom.is_empty() => options.rom = arg,
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    if options.rom.is_empty() {
+        return Err("No ROM given".to_string());
+    }
+    Ok(options)
+}
+
+// "FF80=01"
+fn parse_memory_condition(text: &str) -> Result<(u16, u8), String> {
+    let error = || format!("Bad memory condition {}, expected something like A000=00", text);
+    let (addr, value) = text.split_once('=').ok_or_else(error)?;
+    let addr = u16::from_str_radix(addr.trim_start_matches("0x"), 16).map_err(|_| error())?;
+    let value = u8::from_str_radix(value.trim_start_matches("0x"), 16).map_err(|_| error())?;
+    Ok((addr, value))
+}
+
+// Each line holds buttons from that frame on, until the next line. `-` releases everything.
+//   # frame buttons
+//   60 start
+//   70 -
+//   120 a right
+fn parse_script(text: &str) -> Result<BTreeMap<u64, u8>, String> {
+    let mut script = BTreeMap::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let frame = words
+            .next()
+            .and_then(|frame| frame.parse().ok())
+            .ok_or(format!("line {}: expected a frame number", number + 1))?;
+
+        // 0 = pressed, `↓ ↑ ← → S s B A`
+        let mut pressed = 0xFF;
+        for button in words {
+            let bit = match button.to_ascii_lowercase().as_str() {
+                "-" => continue,
+                "a" => 0,
+                "b" => 1,
+                "select" => 2,
+                "start" => 3,
+                "right" => 4,
+                "left" => 5,
+                "up" => 6,
+                "down" => 7,
+                _ => return Err(format!("line {}: unknown button {}", number + 1, button)),
+            };
+            pressed &= !(1 << bit);
+        }
+        script.insert(frame, pressed);
+    }
+    Ok(script)
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))
+}
+
+// The frame buffer is already 160x144 RGBA
+fn write_png(path: &str, pixels: &[u8]) -> Result<(), String> {
+    let error = |e: &dyn std::fmt::Display| format!("Cannot write {}: {}", path, e);
+    let file = std::fs::File::create(path).map_err(|e| error(&e))?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), 160, 144);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| error(&e))?;
+    writer.write_image_data(pixels).map_err(|e| error(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script = parse_script("# frame buttons\n60 start\n70 -\n\n120 A right # hold\n").unwrap();
+        assert_eq!(
+            script.into_iter().collect::<Vec<_>>(),
+            [(60, 0xF7), (70, 0xFF), (120, 0xEE)]
+        );
+        assert!(parse_script("start").is_err());
+        assert!(parse_script("10 turbo").is_err());
+    }
+
+    #[test]
+    fn test_parse_memory_condition() {
+        assert_eq!(parse_memory_condition("A000=00"), Ok((0xA000, 0x00)));
+        assert_eq!(parse_memory_condition("0xff80=0x1F"), Ok((0xFF80, 0x1F)));
+        assert!(parse_memory_condition("A000").is_err());
+        assert!(parse_memory_condition("10000=00").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use super::CartridgeError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgbSupport {
+    DmgOnly,  // 0x0143 has bit 7 clear
+    Enhanced, // 0x80, also runs on DMG
+    CgbOnly,  // 0xC0
+}
+
+// Metadata at 0x0100-0x014F, readable without building a Cartridge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CartridgeHeader {
+    pub title: String,
+    pub manufacturer_code: String, // Empty on carts from before the CGB
+    pub cgb_support: CgbSupport,
+    pub sgb_support: bool,
+    pub old_licensee_code: u8,
+    pub new_licensee_code: String, // Only used when the old code is 0x33
+    pub cartridge_type: u8,
+    pub mapper: String,
+    pub rom_size: u32, // In bytes, 0 if the code is unknown or unsupported
+    pub ram_size: u32, // In bytes, includes MBC2's built-in RAM. 0 if the code is unsupported too.
+    pub version: u8,
+    pub header_checksum: u8,
+    pub header_checksum_valid: bool,
+    pub global_checksum: u16,
+    pub global_checksum_valid: bool, // Real hardware never checks this one
+}
+
+impl CartridgeHeader {
+    pub fn parse(rom_data: &[u8]) -> Result<Self, CartridgeError> {
+        if rom_data.len() < 0x0150 {
+            return Err(CartridgeError::TooShort);
+        }
+        let cgb_flag = rom_data[0x0143];
+        let cgb_support = match cgb_flag {
+            0xC0 => CgbSupport::CgbOnly,
+            flag if flag & 0x80 != 0 => CgbSupport::Enhanced,
+            _ => CgbSupport::DmgOnly,
+        };
+
+        // The title shrank over time: 16 bytes, then 15 next to the CGB flag, then 11 plus a manufacturer code.
+        // Older CGB carts don't have a code, so only take it when it looks like one.
+        let code = &rom_data[0x013F..0x0143];
+        let has_manufacturer_code = cgb_support != CgbSupport::DmgOnly
+            && code
+                .iter()
+                .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit());
+        let (title_end, manufacturer_code) = match (cgb_support, has_manufacturer_code) {
+            (CgbSupport::DmgOnly, _) => (0x0144, String::new()),
+            (_, true) => (0x013F, ascii(code)),
+            (_, false) => (0x0143, String::new()),
+        };
+
+        let old_licensee_code = rom_data[0x014B];
+        let cartridge_type = rom_data[0x0147];
+        let header_checksum = rom_data[0x014D];
+        let global_checksum = u16::from_be_bytes([rom_data[0x014E], rom_data[0x014F]]);
+        let global_sum = rom_data
+            .iter()
+            .enumerate()
+            .filter(|&(addr, _)| addr != 0x014E && addr != 0x014F)
+            .fold(0u16, |sum, (_, &byte)| sum.wrapping_add(byte as u16));
+
+        Ok(Self {
+            title: ascii(&rom_data[0x0134..title_end]),
+            manufacturer_code,
+            cgb_support,
+            // The boot ROM ignores the SGB flag unless the old licensee code defers to the new one
+            sgb_support: rom_data[0x0146] == 0x03 && old_licensee_code == 0x33,
+            old_licensee_code,
+            new_licensee_code: ascii(&rom_data[0x0144..0x0146]),
+            cartridge_type,
+            mapper: mapper_name(cartridge_type).to_string(),
+            rom_size: rom_size(rom_data[0x0148]),
+            ram_size: match cartridge_type {
+                0x05 | 0x06 => 512,
+                _ => ram_size(rom_data[0x0149]),
+            },
+            version: rom_data[0x014C],
+            header_checksum,
+            header_checksum_valid: header_checksum_of(rom_data) == header_checksum,
+            global_checksum,
+            global_checksum_valid: global_sum == global_checksum,
+        })
+    }
+}
+
+// What the boot ROM compares against 0x014D
+pub fn header_checksum_of(rom_data: &[u8]) -> u8 {
+    rom_data[0x0134..=0x014C]
+        .iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_sub(byte).wrapping_sub(1))
+}
+
+// Stops at the first NUL, anything unprintable becomes a space
+fn ascii(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|&&byte| byte != 0)
+        .map(|&byte| if byte.is_ascii_graphic() { byte as char } else { ' ' })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+// 32 KiB << n. The unofficial 0x52-0x54 sizes some docs list are 0, Cartridge::new rejects them.
+fn rom_size(code: u8) -> u32 {
+    match code {
+        0x00..=0x08 => 0x8000 << code,
+        _ => 0,
+    }
+}
+
+// The mappers' own table, so a code they reject (like 0x01, 2 KiB) shows up as 0 here too
+fn ram_size(code: u8) -> u32 {
+    super::mbc::num_of_ram_banks(code).map_or(0, |banks| banks as u32 * 0x2000)
+}
+
+pub fn mapper_name(cartridge_type: u8) -> &'static str {
+    match cartridge_type {
+        0x00 => "ROM ONLY",
+        0x01 => "MBC1",
+        0x02 => "MBC1+RAM",
+        0x03 => "MBC1+RAM+BATTERY",
+        0x05 => "MBC2",
+        0x06 => "MBC2+BATTERY",
+        0x08 => "ROM+RAM",
+        0x09 => "ROM+RAM+BATTERY",
+        0x0B => "MMM01",
+        0x0C => "MMM01+RAM",
+        0x0D => "MMM01+RAM+BATTERY",
+        0x0F => "MBC3+TIMER+BATTERY",
+        0x10 => "MBC3+TIMER+RAM+BATTERY",
+        0x11 => "MBC3",
+        0x12 => "MBC3+RAM",
+        0x13 => "MBC3+RAM+BATTERY",
+        0x19 => "MBC5",
+        0x1A => "MBC5+RAM",
+        0x1B => "MBC5+RAM+BATTERY",
+        0x1C => "MBC5+RUMBLE",
+        0x1D => "MBC5+RUMBLE+RAM",
+        0x1E => "MBC5+RUMBLE+RAM+BATTERY",
+        0x20 => "MBC6",
+        0x22 => "MBC7+SENSOR+RUMBLE+RAM+BATTERY",
+        0xFC => "POCKET CAMERA",
+        0xFD => "BANDAI TAMA5",
+        0xFE => "HuC3",
+        0xFF => "HuC1+RAM+BATTERY",
+        _ => "UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tetris_header() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!(header.title, "TETRIS");
+        assert_eq!(header.manufacturer_code, "");
+        assert_eq!(header.cgb_support, CgbSupport::DmgOnly);
+        assert!(!header.sgb_support);
+        assert_eq!(header.old_licensee_code, 0x01);
+        assert_eq!(header.mapper, "ROM ONLY");
+        assert_eq!(header.rom_size, 0x8000);
+        assert_eq!(header.ram_size, 0);
+        assert!(header.header_checksum_valid);
+        assert!(header.global_checksum_valid);
+    }
+
+    #[test]
+    fn test_cgb_title_and_manufacturer_code() {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0134..0x0143].copy_from_slice(b"POKEMON_GLDAAUE");
+        rom[0x0143] = 0x80;
+        rom[0x0144..0x0146].copy_from_slice(b"01");
+        rom[0x0146] = 0x03;
+        rom[0x0147] = 0x10;
+        rom[0x0149] = 0x03;
+        rom[0x014B] = 0x33;
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!(header.title, "POKEMON_GLD");
+        assert_eq!(header.manufacturer_code, "AAUE");
+        assert_eq!(header.cgb_support, CgbSupport::Enhanced);
+        assert!(header.sgb_support);
+        assert_eq!(header.new_licensee_code, "01");
+        assert_eq!(header.mapper, "MBC3+TIMER+RAM+BATTERY");
+        assert_eq!(header.ram_size, 0x8000);
+        assert!(!header.header_checksum_valid);
+        assert!(!header.global_checksum_valid);
+
+        // Lowercase can't be a manufacturer code, so it stays in the title
+        rom[0x013F..0x0143].copy_from_slice(b"d ab");
+        rom[0x0143] = 0xC0;
+        rom[0x014D] = header_checksum_of(&rom);
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!(header.title, "POKEMON_GLDd ab");
+        assert_eq!(header.manufacturer_code, "");
+        assert_eq!(header.cgb_support, CgbSupport::CgbOnly);
+        assert!(header.header_checksum_valid);
+    }
+
+    #[test]
+    fn test_sizes_match_the_loader() {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0148] = 0x52;
+        rom[0x0149] = 0x01;
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!(header.rom_size, 0);
+        assert_eq!(header.ram_size, 0);
+
+        rom[0x0148] = 0x08;
+        rom[0x0149] = 0x05;
+        let header = CartridgeHeader::parse(&rom).unwrap();
+        assert_eq!(header.rom_size, 8 << 20);
+        assert_eq!(header.ram_size, 0x10000);
+    }
+}
@@ -1,11 +1,18 @@
-use super::Mbc;
+use super::{Mbc, load_ram};
+use crate::savestate::{StateError, StateReader, StateWriter};
 
 pub struct Mbc0 {
     rom: Vec<u8>,
+    ram: Vec<u8>, // Up to 8 KiB, always accessible
+    ram_persistent: bool,
 }
 impl Mbc0 {
-    pub fn new(rom_data: Vec<u8>) -> Self {
-        Mbc0 { rom: rom_data }
+    pub fn new(rom_data: Vec<u8>, has_ram: bool, ram_persistent: bool) -> Self {
+        Mbc0 {
+            rom: rom_data,
+            ram: if has_ram { vec![0; 0x2000] } else { Vec::new() },
+            ram_persistent,
+        }
     }
 }
 impl Mbc for Mbc0 {
@@ -15,10 +22,55 @@ impl Mbc for Mbc0 {
     fn write_rom(&mut self, _addr: u16, _value: u8) {
         // MBC0 has no registers to write to
     }
-    fn read_ram(&self, _addr: u16) -> u8 {
-        0xFF
+    fn read_ram(&self, addr: u16) -> u8 {
+        self.ram.get(addr as usize - 0xA000).copied().unwrap_or(0xFF)
     }
-    fn write_ram(&mut self, _addr: u16, _value: u8) {
-        // No support for MBC0 + RAM
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        match self.ram.get_mut(addr as usize - 0xA000) {
+            Some(byte) => std::mem::replace(byte, value) != value,
+            None => false,
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        self.ram_persistent
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        if !self.ram_persistent {
+            return Vec::new();
+        }
+        self.ram.clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        if self.ram_persistent {
+            load_ram(&mut self.ram, data);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.vec(&self.ram);
+    }
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.vec_into(&mut self.ram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ram() {
+        let mut without_ram = Mbc0::new(vec![0; 0x8000], false, false);
+        without_ram.write_ram(0xA000, 0x12);
+        assert_eq!(without_ram.read_ram(0xA000), 0xFF);
+
+        let mut with_ram = Mbc0::new(vec![0; 0x8000], true, true);
+        with_ram.write_ram(0xA000, 0x12);
+        with_ram.write_ram(0xBFFF, 0x34);
+        assert_eq!(with_ram.read_ram(0xA000), 0x12);
+        assert_eq!(with_ram.read_ram(0xBFFF), 0x34);
     }
 }
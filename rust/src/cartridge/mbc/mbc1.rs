@@ -1,4 +1,6 @@
-use super::Mbc;
+use super::{Mbc, load_ram, num_of_ram_banks};
+use crate::cartridge::CartridgeError;
+use crate::savestate::{StateError, StateReader, StateWriter};
 
 pub struct Mbc1 {
     rom: Vec<u8>,
@@ -9,21 +11,16 @@ pub struct Mbc1 {
     bank_reg_1: u8,
     bank_reg_2: u8,
     advanced_mode: bool,
+    multicart: bool, // MBC1M: bank_reg_2 sits on top of a 4-bit bank_reg_1
     ram_persistent: bool,
 }
 
 impl Mbc1 {
-    pub fn new(rom_data: Vec<u8>, ram_persistent: bool) -> Self {
+    pub fn new(rom_data: Vec<u8>, ram_persistent: bool) -> Result<Self, CartridgeError> {
         let num_of_rom_banks = 0b1 << (rom_data[0x0148] + 1);
-        let num_of_ram_banks = match rom_data[0x0149] {
-            0x00 => 0,
-            0x02 => 1,
-            0x03 => 4,
-            0x04 => 16,
-            0x05 => 8,
-            _ => panic!("Unsupported RAM size"),
-        };
-        Mbc1 {
+        let num_of_ram_banks = num_of_ram_banks(rom_data[0x0149])?;
+        let multicart = Self::is_multicart(&rom_data);
+        Ok(Mbc1 {
             rom: rom_data,
             num_of_rom_banks,
             ram: vec![0; num_of_ram_banks * 0x2000],
@@ -32,8 +29,18 @@ impl Mbc1 {
             bank_reg_1: 0x01, // 5 bits used
             bank_reg_2: 0x00, // 2 bits used
             advanced_mode: false,
+            multicart,
             ram_persistent,
-        }
+        })
+    }
+
+    // Multicarts are 1 MiB collections with another game (and its Nintendo logo) starting at bank 0x10
+    fn is_multicart(rom_data: &[u8]) -> bool {
+        rom_data.len() == 0x100000 && rom_data[0x40104..0x40134] == rom_data[0x0104..0x0134]
+    }
+
+    fn bank_shift(&self) -> u8 {
+        if self.multicart { 4 } else { 5 }
     }
 }
 
@@ -42,14 +49,21 @@ impl Mbc for Mbc1 {
         match addr {
             0x0000..=0x3FFF => {
                 let target_bank = if self.advanced_mode {
-                    (self.bank_reg_2 << 5) as usize % self.num_of_rom_banks
+                    (self.bank_reg_2 << self.bank_shift()) as usize % self.num_of_rom_banks
                 } else {
                     0
                 };
                 self.rom[target_bank * 0x4000 + addr as usize]
             }
             0x4000..=0x7FFF => {
-                let target_bank = ((self.bank_reg_2 << 5) | self.bank_reg_1) as usize % self.num_of_rom_banks;
+                // The zero check in write_rom still sees all 5 bits on multicarts
+                let bank_reg_1 = if self.multicart {
+                    self.bank_reg_1 & 0x0F
+                } else {
+                    self.bank_reg_1
+                };
+                let target_bank =
+                    ((self.bank_reg_2 << self.bank_shift()) | bank_reg_1) as usize % self.num_of_rom_banks;
                 self.rom[target_bank * 0x4000 + (addr as usize - 0x4000)]
             }
             _ => unreachable!(),
@@ -81,9 +95,9 @@ impl Mbc for Mbc1 {
         self.ram[bank * 0x2000 + (addr as usize - 0xA000)]
     }
 
-    fn write_ram(&mut self, addr: u16, value: u8) {
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
         if !self.ram_enabled || self.num_of_ram_banks == 0 {
-            return;
+            return false;
         }
         let bank = if self.advanced_mode {
             self.bank_reg_2 as usize % self.num_of_ram_banks
@@ -91,6 +105,112 @@ impl Mbc for Mbc1 {
             0
         };
 
-        self.ram[bank * 0x2000 + (addr as usize - 0xA000)] = value;
+        std::mem::replace(&mut self.ram[bank * 0x2000 + (addr as usize - 0xA000)], value) != value
+    }
+
+    fn has_battery(&self) -> bool {
+        self.ram_persistent
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        if !self.ram_persistent {
+            return Vec::new();
+        }
+        self.ram.clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        if self.ram_persistent {
+            load_ram(&mut self.ram, data);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.vec(&self.ram);
+        state.bool(self.ram_enabled);
+        state.u8(self.bank_reg_1);
+        state.u8(self.bank_reg_2);
+        state.bool(self.advanced_mode);
+    }
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.vec_into(&mut self.ram)?;
+        self.ram_enabled = state.bool()?;
+        self.bank_reg_1 = state.u8()?;
+        self.bank_reg_2 = state.u8()?;
+        self.advanced_mode = state.bool()?;
+        if !(0x01..=0x1F).contains(&self.bank_reg_1) || self.bank_reg_2 > 0x03 {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::numbered_rom;
+
+    // 1 MiB, every bank starts with its number
+    fn rom(multicart: bool) -> Vec<u8> {
+        let mut rom = numbered_rom(64);
+        rom[0x0147] = 0x01;
+        rom[0x0148] = 0x05;
+        for i in 0..0x30 {
+            rom[0x0104 + i] = i as u8 + 1;
+            if multicart {
+                rom[0x40104 + i] = i as u8 + 1;
+            }
+        }
+        rom
+    }
+
+    #[test]
+    fn test_five_bit_banking() {
+        let mut mbc = Mbc1::new(rom(false), false).unwrap();
+        assert!(!mbc.multicart);
+        mbc.write_rom(0x2000, 0x12);
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x32);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x20);
+    }
+
+    #[test]
+    fn test_save_data() {
+        let mut rom = rom(false);
+        rom[0x0149] = 0x03;
+        let mut mbc = Mbc1::new(rom.clone(), true).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x6000, 0x01);
+        mbc.write_rom(0x4000, 0x02);
+        mbc.write_ram(0xA010, 0x5A);
+        let save = mbc.save_data();
+        assert_eq!(save.len(), 4 * 0x2000);
+        assert_eq!(save[2 * 0x2000 + 0x10], 0x5A);
+
+        let mut loaded = Mbc1::new(rom.clone(), true).unwrap();
+        loaded.load_save_data(&save);
+        loaded.write_rom(0x0000, 0x0A);
+        loaded.write_rom(0x6000, 0x01);
+        loaded.write_rom(0x4000, 0x02);
+        assert_eq!(loaded.read_ram(0xA010), 0x5A);
+
+        // Without a battery there is nothing to save
+        assert!(Mbc1::new(rom, false).unwrap().save_data().is_empty());
+    }
+
+    #[test]
+    fn test_multicart_banking() {
+        let mut mbc = Mbc1::new(rom(true), false).unwrap();
+        assert!(mbc.multicart);
+        // Bit 4 of bank_reg_1 isn't wired
+        mbc.write_rom(0x2000, 0x12);
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x12);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.read_rom(0x0000), 0x10);
+        // 0x10 isn't treated as 0, so the second game can map its bank 0 up top
+        mbc.write_rom(0x2000, 0x10);
+        assert_eq!(mbc.read_rom(0x4000), 0x10);
     }
 }
@@ -0,0 +1,146 @@
+use super::{Mbc, load_ram};
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+pub struct Mbc2 {
+    rom: Vec<u8>,
+    num_of_rom_banks: usize,
+    ram: [u8; 512], // Built-in, only the lower nibbles exist
+    ram_enabled: bool,
+    rom_bank: u8, // 4 bits used
+    ram_persistent: bool,
+}
+
+impl Mbc2 {
+    pub fn new(rom_data: Vec<u8>, ram_persistent: bool) -> Self {
+        let num_of_rom_banks = 0b1 << (rom_data[0x0148] + 1);
+        Mbc2 {
+            rom: rom_data,
+            num_of_rom_banks,
+            ram: [0; 512],
+            ram_enabled: false,
+            rom_bank: 0x01,
+            ram_persistent,
+        }
+    }
+}
+
+impl Mbc for Mbc2 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom[addr as usize],
+            0x4000..=0x7FFF => {
+                let target_bank = self.rom_bank as usize % self.num_of_rom_banks;
+                self.rom[target_bank * 0x4000 + (addr as usize - 0x4000)]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            // Address bit 8 picks the register: clear for RAM enable, set for ROM bank
+            0x0000..=0x3FFF => {
+                if addr & 0x0100 == 0 {
+                    self.ram_enabled = value & 0x0F == 0x0A;
+                } else {
+                    self.rom_bank = if value & 0x0F == 0 { 0x01 } else { value & 0x0F };
+                }
+            }
+            0x4000..=0x7FFF => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        // 512 bytes mirrored across 0xA000-0xBFFF, upper nibbles are open bus
+        self.ram[addr as usize & 0x1FF] | 0xF0
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+        std::mem::replace(&mut self.ram[addr as usize & 0x1FF], value & 0x0F) != value & 0x0F
+    }
+
+    fn has_battery(&self) -> bool {
+        self.ram_persistent
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        if !self.ram_persistent {
+            return Vec::new();
+        }
+        self.ram.to_vec()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        if self.ram_persistent {
+            load_ram(&mut self.ram, data);
+            self.ram.iter_mut().for_each(|byte| *byte &= 0x0F);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.bytes(&self.ram);
+        state.bool(self.ram_enabled);
+        state.u8(self.rom_bank);
+    }
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.bytes(&mut self.ram)?;
+        self.ram_enabled = state.bool()?;
+        self.rom_bank = state.u8()?;
+        if !(0x01..=0x0F).contains(&self.rom_bank) {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::numbered_rom;
+
+    fn mbc2() -> Mbc2 {
+        let mut rom = numbered_rom(16);
+        rom[0x0147] = 0x06;
+        rom[0x0148] = 0x03;
+        Mbc2::new(rom, true)
+    }
+
+    #[test]
+    fn test_register_decoding() {
+        let mut mbc = mbc2();
+        // Bit 8 set: ROM bank, even in the lower half
+        mbc.write_rom(0x0100, 0x05);
+        assert_eq!(mbc.read_rom(0x4000), 5);
+        mbc.write_rom(0x3FFF, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+        mbc.write_rom(0x21FF, 0xFF);
+        assert_eq!(mbc.read_rom(0x4000), 15);
+
+        // Bit 8 clear: RAM enable, even in the upper half
+        mbc.write_rom(0x2000, 0x0A);
+        assert_eq!(mbc.read_rom(0x4000), 15);
+        mbc.write_ram(0xA000, 0x05);
+        assert_eq!(mbc.read_ram(0xA000), 0xF5);
+        mbc.write_rom(0x00FF, 0x00);
+        assert_eq!(mbc.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_half_byte_ram_mirrors() {
+        let mut mbc = mbc2();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(0xA1FF, 0xAB);
+        assert_eq!(mbc.read_ram(0xA1FF), 0xFB);
+        assert_eq!(mbc.read_ram(0xA3FF), 0xFB);
+        assert_eq!(mbc.read_ram(0xBFFF), 0xFB);
+        mbc.write_ram(0xB200, 0x03);
+        assert_eq!(mbc.read_ram(0xA000), 0xF3);
+    }
+}
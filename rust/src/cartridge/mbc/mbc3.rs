@@ -0,0 +1,450 @@
+use super::{Mbc, load_ram, num_of_ram_banks};
+use crate::cartridge::CartridgeError;
+use crate::mmu::CLOCK_RATE;
+use crate::savestate::{StateError, StateReader, StateWriter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Clock registers, also used for the latched copy
+#[derive(Clone, Copy, Default)]
+struct RtcRegisters {
+    seconds: u8, // 6 bits
+    minutes: u8, // 6 bits
+    hours: u8,   // 5 bits
+    days: u16,   // 9 bits
+    halt: bool,
+    day_carry: bool,
+}
+impl RtcRegisters {
+    // `reg` is 0x08 to 0x0C for S, M, H, DL and DH
+    fn read(&self, reg: u8) -> u8 {
+        match reg {
+            0x08 => self.seconds,
+            0x09 => self.minutes,
+            0x0A => self.hours,
+            0x0B => self.days as u8,
+            0x0C => ((self.day_carry as u8) << 7) | ((self.halt as u8) << 6) | (self.days >> 8) as u8,
+            _ => unreachable!(),
+        }
+    }
+
+    fn write(&mut self, reg: u8, value: u8) {
+        match reg {
+            0x08 => self.seconds = value & 0x3F,
+            0x09 => self.minutes = value & 0x3F,
+            0x0A => self.hours = value & 0x1F,
+            0x0B => self.days = (self.days & 0x100) | value as u16,
+            0x0C => {
+                self.days = (self.days & 0xFF) | ((value as u16 & 0b1) << 8);
+                self.halt = value & 0b0100_0000 != 0;
+                self.day_carry = value & 0b1000_0000 != 0;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Counters only carry when they hit their real limit, out of range values count up until they wrap
+    fn tick(&mut self) {
+        self.seconds = (self.seconds + 1) & 0x3F;
+        if self.seconds != 60 {
+            return;
+        }
+        self.seconds = 0;
+        self.minutes = (self.minutes + 1) & 0x3F;
+        if self.minutes != 60 {
+            return;
+        }
+        self.minutes = 0;
+        self.hours = (self.hours + 1) & 0x1F;
+        if self.hours != 24 {
+            return;
+        }
+        self.hours = 0;
+        self.days = (self.days + 1) & 0x1FF;
+        if self.days == 0 {
+            self.day_carry = true;
+        }
+    }
+
+    fn advance(&mut self, mut seconds: u64) {
+        // Step out of range values one second at a time, at most a few hours
+        while seconds > 0 && (self.seconds >= 60 || self.minutes >= 60 || self.hours >= 24) {
+            self.tick();
+            seconds -= 1;
+        }
+        let total_seconds = self.seconds as u64 + seconds;
+        let total_minutes = self.minutes as u64 + total_seconds / 60;
+        let total_hours = self.hours as u64 + total_minutes / 60;
+        let total_days = self.days as u64 + total_hours / 24;
+        self.seconds = (total_seconds % 60) as u8;
+        self.minutes = (total_minutes % 60) as u8;
+        self.hours = (total_hours % 24) as u8;
+        self.days = (total_days % 512) as u16;
+        if total_days >= 512 {
+            self.day_carry = true;
+        }
+    }
+}
+
+// Real-time clock, counts while the game runs and catches up with the wall clock when a save is loaded
+struct Rtc {
+    current: RtcRegisters,
+    latched: RtcRegisters,
+    latch_armed: bool, // 0x00 was written, 0x01 latches next
+    sub_second: u32,   // T-cycles into the current second
+}
+impl Rtc {
+    fn new() -> Self {
+        Self {
+            current: RtcRegisters::default(),
+            latched: RtcRegisters::default(),
+            latch_armed: false,
+            sub_second: 0,
+        }
+    }
+
+    fn step(&mut self, t_cycles: u32) {
+        if self.current.halt {
+            return;
+        }
+        self.sub_second += t_cycles;
+        while self.sub_second >= CLOCK_RATE {
+            self.sub_second -= CLOCK_RATE;
+            self.current.tick();
+        }
+    }
+
+    fn write_latch(&mut self, value: u8) {
+        if self.latch_armed && value == 0x01 {
+            self.latched = self.current;
+        }
+        self.latch_armed = value == 0x00;
+    }
+
+    fn write(&mut self, reg: u8, value: u8) {
+        // Writing seconds resets the divider
+        if reg == 0x08 {
+            self.sub_second = 0;
+        }
+        self.current.write(reg, value);
+    }
+
+    // Same 48-byte layout as VBA-M and BGB: current and latched S, M, H, DL, DH as u32, then a u64 UNIX timestamp
+    fn save(&self, now: u64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(48);
+        for registers in [&self.current, &self.latched] {
+            for reg in 0x08..=0x0C {
+                data.extend_from_slice(&(registers.read(reg) as u32).to_le_bytes());
+            }
+        }
+        data.extend_from_slice(&now.to_le_bytes());
+        data
+    }
+
+    fn load(&mut self, data: &[u8], now: u64) {
+        if data.len() < 48 {
+            return;
+        }
+        let word = |i: usize| data[i * 4];
+        for reg in 0x08..=0x0C {
+            let i = (reg - 0x08) as usize;
+            self.current.write(reg, word(i));
+            self.latched.write(reg, word(i + 5));
+        }
+        let timestamp = u64::from_le_bytes(data[40..48].try_into().unwrap());
+        if !self.current.halt {
+            self.current.advance(now.saturating_sub(timestamp));
+        }
+        self.sub_second = 0;
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+pub struct Mbc3 {
+    rom: Vec<u8>,
+    num_of_rom_banks: usize,
+    ram: Vec<u8>,
+    num_of_ram_banks: usize,
+    ram_enabled: bool, // Also gates the RTC registers
+    rom_bank: u8,      // 7 bits used
+    ram_bank: u8,      // 0x00-0x03: RAM bank, 0x08-0x0C: RTC register
+    rtc: Option<Rtc>,
+    ram_persistent: bool,
+}
+
+impl Mbc3 {
+    pub fn new(rom_data: Vec<u8>, has_rtc: bool, ram_persistent: bool) -> Result<Self, CartridgeError> {
+        let num_of_rom_banks = 0b1 << (rom_data[0x0148] + 1);
+        let num_of_ram_banks = num_of_ram_banks(rom_data[0x0149])?;
+        Ok(Mbc3 {
+            rom: rom_data,
+            num_of_rom_banks,
+            ram: vec![0; num_of_ram_banks * 0x2000],
+            num_of_ram_banks,
+            ram_enabled: false,
+            rom_bank: 0x01,
+            ram_bank: 0x00,
+            rtc: has_rtc.then(Rtc::new),
+            ram_persistent,
+        })
+    }
+}
+
+impl Mbc for Mbc3 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom[addr as usize],
+            0x4000..=0x7FFF => {
+                let target_bank = self.rom_bank as usize % self.num_of_rom_banks;
+                self.rom[target_bank * 0x4000 + (addr as usize - 0x4000)]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = if value & 0x7F == 0 { 0x01 } else { value & 0x7F },
+            0x4000..=0x5FFF => self.ram_bank = value,
+            0x6000..=0x7FFF => {
+                if let Some(rtc) = &mut self.rtc {
+                    rtc.write_latch(value);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        match (self.ram_bank, &self.rtc) {
+            (0x00..=0x03, _) if self.num_of_ram_banks > 0 => {
+                let bank = self.ram_bank as usize % self.num_of_ram_banks;
+                self.ram[bank * 0x2000 + (addr as usize - 0xA000)]
+            }
+            (0x08..=0x0C, Some(rtc)) => rtc.latched.read(self.ram_bank),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+        match (self.ram_bank, &mut self.rtc) {
+            (0x00..=0x03, _) if self.num_of_ram_banks > 0 => {
+                let bank = self.ram_bank as usize % self.num_of_ram_banks;
+                std::mem::replace(&mut self.ram[bank * 0x2000 + (addr as usize - 0xA000)], value) != value
+            }
+            (0x08..=0x0C, Some(rtc)) => {
+                rtc.write(self.ram_bank, value);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn step(&mut self, t_cycles: u32) {
+        if let Some(rtc) = &mut self.rtc {
+            rtc.step(t_cycles);
+        }
+    }
+
+    fn has_battery(&self) -> bool {
+        self.ram_persistent
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        if !self.ram_persistent {
+            return Vec::new();
+        }
+        let mut data = self.ram.clone();
+        if let Some(rtc) = &self.rtc {
+            data.extend(rtc.save(unix_time()));
+        }
+        data
+    }
+
+    // The RTC catches up with the time passed since the save was written
+    fn load_save_data(&mut self, data: &[u8]) {
+        if !self.ram_persistent {
+            return;
+        }
+        load_ram(&mut self.ram, data);
+        if let (Some(rtc), Some(rtc_data)) = (&mut self.rtc, data.get(self.ram.len()..)) {
+            rtc.load(rtc_data, unix_time());
+        }
+    }
+
+    // Unlike .sav files, the clock doesn't catch up with the wall clock on load
+    fn save_state(&self, state: &mut StateWriter) {
+        state.vec(&self.ram);
+        state.bool(self.ram_enabled);
+        state.u8(self.rom_bank);
+        state.u8(self.ram_bank);
+        if let Some(rtc) = &self.rtc {
+            for registers in [&rtc.current, &rtc.latched] {
+                for reg in 0x08..=0x0C {
+                    state.u8(registers.read(reg));
+                }
+            }
+            state.bool(rtc.latch_armed);
+            state.u32(rtc.sub_second);
+        }
+    }
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.vec_into(&mut self.ram)?;
+        self.ram_enabled = state.bool()?;
+        self.rom_bank = state.u8()?;
+        self.ram_bank = state.u8()?;
+        if !(0x01..=0x7F).contains(&self.rom_bank) {
+            return Err(StateError::Corrupt);
+        }
+        if let Some(rtc) = &mut self.rtc {
+            for registers in [&mut rtc.current, &mut rtc.latched] {
+                for reg in 0x08..=0x0C {
+                    registers.write(reg, state.u8()?);
+                }
+            }
+            rtc.latch_armed = state.bool()?;
+            rtc.sub_second = state.u32()?;
+            if rtc.sub_second >= CLOCK_RATE {
+                return Err(StateError::Corrupt);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::numbered_rom;
+
+    fn mbc3() -> Mbc3 {
+        let mut rom = numbered_rom(128);
+        rom[0x0147] = 0x10;
+        rom[0x0148] = 0x06;
+        rom[0x0149] = 0x03;
+        let mut mbc = Mbc3::new(rom, true, true).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc
+    }
+
+    fn read_rtc(mbc: &mut Mbc3, reg: u8) -> u8 {
+        mbc.write_rom(0x4000, reg);
+        mbc.read_ram(0xA000)
+    }
+
+    fn latch(mbc: &mut Mbc3) {
+        mbc.write_rom(0x6000, 0x00);
+        mbc.write_rom(0x6000, 0x01);
+    }
+
+    #[test]
+    fn test_rom_and_ram_banks() {
+        let mut mbc = mbc3();
+        assert_eq!(mbc.read_rom(0x4000), 1);
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(mbc.read_rom(0x4000), 1);
+        mbc.write_rom(0x2000, 0x7F);
+        assert_eq!(mbc.read_rom(0x4000), 127);
+        mbc.write_rom(0x2000, 0xC5); // Only 7 bits
+        assert_eq!(mbc.read_rom(0x4000), 0x45);
+
+        for bank in 0..4 {
+            mbc.write_rom(0x4000, bank);
+            mbc.write_ram(0xA123, bank + 0x10);
+        }
+        for bank in 0..4 {
+            mbc.write_rom(0x4000, bank);
+            assert_eq!(mbc.read_ram(0xA123), bank + 0x10);
+        }
+        mbc.write_rom(0x0000, 0x00);
+        assert_eq!(mbc.read_ram(0xA123), 0xFF);
+    }
+
+    #[test]
+    fn test_rtc_latch() {
+        let mut mbc = mbc3();
+        mbc.step(CLOCK_RATE * 3);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0); // Not latched yet
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 3);
+        mbc.step(CLOCK_RATE);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 3);
+
+        // 0x01 alone doesn't latch
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 3);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 4);
+    }
+
+    #[test]
+    fn test_rtc_rollover_and_halt() {
+        let mut mbc = mbc3();
+        mbc.write_rom(0x4000, 0x08);
+        mbc.write_ram(0xA000, 59);
+        mbc.write_rom(0x4000, 0x09);
+        mbc.write_ram(0xA000, 59);
+        mbc.write_rom(0x4000, 0x0A);
+        mbc.write_ram(0xA000, 23);
+        mbc.write_rom(0x4000, 0x0B);
+        mbc.write_ram(0xA000, 0xFF);
+        mbc.write_rom(0x4000, 0x0C);
+        mbc.write_ram(0xA000, 0x01);
+
+        mbc.step(CLOCK_RATE);
+        latch(&mut mbc);
+        for reg in 0x08..=0x0B {
+            assert_eq!(read_rtc(&mut mbc, reg), 0);
+        }
+        assert_eq!(read_rtc(&mut mbc, 0x0C), 0x80); // Day carry
+
+        // Halted clock doesn't count
+        mbc.write_ram(0xA000, 0x40);
+        mbc.step(CLOCK_RATE * 2);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x0C), 0x40);
+
+        // Out of range seconds wrap at 64 without carrying
+        mbc.write_rom(0x4000, 0x0C);
+        mbc.write_ram(0xA000, 0x00);
+        mbc.write_rom(0x4000, 0x08);
+        mbc.write_ram(0xA000, 63);
+        mbc.step(CLOCK_RATE);
+        latch(&mut mbc);
+        assert_eq!(read_rtc(&mut mbc, 0x08), 0);
+        assert_eq!(read_rtc(&mut mbc, 0x09), 0);
+    }
+
+    #[test]
+    fn test_rtc_catch_up() {
+        let mut mbc = mbc3();
+        mbc.step(CLOCK_RATE * 10);
+        assert_eq!(mbc.save_data().len(), 4 * 0x2000 + 48);
+
+        // Loaded a day, an hour, a minute and 5 seconds later
+        let data = mbc.rtc.as_ref().unwrap().save(1_000_000);
+        let mut loaded = mbc3();
+        loaded
+            .rtc
+            .as_mut()
+            .unwrap()
+            .load(&data, 1_000_000 + 86400 + 3600 + 60 + 5);
+        latch(&mut loaded);
+        assert_eq!(read_rtc(&mut loaded, 0x08), 15);
+        assert_eq!(read_rtc(&mut loaded, 0x09), 1);
+        assert_eq!(read_rtc(&mut loaded, 0x0A), 1);
+        assert_eq!(read_rtc(&mut loaded, 0x0B), 1);
+    }
+}
@@ -0,0 +1,193 @@
+use super::{Mbc, load_ram, num_of_ram_banks};
+use crate::RumbleSignal;
+use crate::cartridge::CartridgeError;
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+pub struct Mbc5 {
+    rom: Vec<u8>,
+    num_of_rom_banks: usize,
+    ram: Vec<u8>,
+    num_of_ram_banks: usize,
+    ram_enabled: bool,
+    rom_bank: u16, // 9 bits used, bank 0 is allowed
+    ram_bank: u8,  // 4 bits used, 3 on rumble carts
+    rumble: Option<RumbleSignal>,
+    ram_persistent: bool,
+}
+
+impl Mbc5 {
+    // `rumble` is set for carts with a motor
+    pub fn new(rom_data: Vec<u8>, ram_persistent: bool, rumble: Option<RumbleSignal>) -> Result<Self, CartridgeError> {
+        let num_of_rom_banks = 0b1 << (rom_data[0x0148] + 1);
+        let num_of_ram_banks = num_of_ram_banks(rom_data[0x0149])?;
+        Ok(Mbc5 {
+            rom: rom_data,
+            num_of_rom_banks,
+            ram: vec![0; num_of_ram_banks * 0x2000],
+            num_of_ram_banks,
+            ram_enabled: false,
+            rom_bank: 0x01,
+            ram_bank: 0x00,
+            rumble,
+            ram_persistent,
+        })
+    }
+
+    fn ram_offset(&self, addr: u16) -> usize {
+        let bank = self.ram_bank as usize % self.num_of_ram_banks;
+        bank * 0x2000 + (addr as usize - 0xA000)
+    }
+}
+
+impl Mbc for Mbc5 {
+    fn read_rom(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => self.rom[addr as usize],
+            0x4000..=0x7FFF => {
+                let target_bank = self.rom_bank as usize % self.num_of_rom_banks;
+                self.rom[target_bank * 0x4000 + (addr as usize - 0x4000)]
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_rom(&mut self, addr: u16, value: u8) {
+        match addr {
+            // Unlike MBC1 and MBC3, only 0x0A enables RAM
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => self.rom_bank = (self.rom_bank & 0xFF) | ((value as u16 & 0b1) << 8),
+            0x4000..=0x5FFF => match &self.rumble {
+                // Bit 3 drives the motor instead of selecting a bank
+                Some(rumble) => {
+                    rumble.set(value & 0b0000_1000 != 0);
+                    self.ram_bank = value & 0x07;
+                }
+                None => self.ram_bank = value & 0x0F,
+            },
+            0x6000..=0x7FFF => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn read_ram(&self, addr: u16) -> u8 {
+        if !self.ram_enabled || self.num_of_ram_banks == 0 {
+            return 0xFF;
+        }
+        self.ram[self.ram_offset(addr)]
+    }
+
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool {
+        if !self.ram_enabled || self.num_of_ram_banks == 0 {
+            return false;
+        }
+        let offset = self.ram_offset(addr);
+        std::mem::replace(&mut self.ram[offset], value) != value
+    }
+
+    fn has_battery(&self) -> bool {
+        self.ram_persistent
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        if !self.ram_persistent {
+            return Vec::new();
+        }
+        self.ram.clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        if self.ram_persistent {
+            load_ram(&mut self.ram, data);
+        }
+    }
+
+    fn save_state(&self, state: &mut StateWriter) {
+        state.vec(&self.ram);
+        state.bool(self.ram_enabled);
+        state.u16(self.rom_bank);
+        state.u8(self.ram_bank);
+        state.bool(self.rumble.as_ref().is_some_and(|rumble| rumble.get()));
+    }
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.vec_into(&mut self.ram)?;
+        self.ram_enabled = state.bool()?;
+        self.rom_bank = state.u16()?;
+        self.ram_bank = state.u8()?;
+        if self.rom_bank > 0x1FF || self.ram_bank > 0x0F {
+            return Err(StateError::Corrupt);
+        }
+        let motor = state.bool()?;
+        if let Some(rumble) = &self.rumble {
+            rumble.set(motor);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::mbc::numbered_rom;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn mbc5(rumble: Option<RumbleSignal>) -> Mbc5 {
+        // 8 MiB
+        let mut rom = numbered_rom(512);
+        rom[0x0148] = 0x08;
+        rom[0x0149] = 0x04;
+        let mut mbc = Mbc5::new(rom, true, rumble).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc
+    }
+
+    fn upper_bank(mbc: &Mbc5) -> u16 {
+        u16::from_le_bytes([mbc.read_rom(0x4000), mbc.read_rom(0x4001)])
+    }
+
+    #[test]
+    fn test_rom_banks() {
+        let mut mbc = mbc5(None);
+        assert_eq!(upper_bank(&mbc), 1);
+        mbc.write_rom(0x2000, 0x00);
+        assert_eq!(upper_bank(&mbc), 0);
+        mbc.write_rom(0x2000, 0xFF);
+        mbc.write_rom(0x3000, 0x01);
+        assert_eq!(upper_bank(&mbc), 0x1FF);
+        mbc.write_rom(0x2000, 0x23);
+        assert_eq!(upper_bank(&mbc), 0x123);
+        mbc.write_rom(0x3000, 0x00);
+        assert_eq!(upper_bank(&mbc), 0x23);
+    }
+
+    #[test]
+    fn test_ram_banks() {
+        let mut mbc = mbc5(None);
+        for bank in 0..16 {
+            mbc.write_rom(0x4000, bank);
+            mbc.write_ram(0xBFFF, bank + 0x20);
+        }
+        for bank in 0..16 {
+            mbc.write_rom(0x4000, bank);
+            assert_eq!(mbc.read_ram(0xBFFF), bank + 0x20);
+        }
+        // Only exactly 0x0A enables RAM
+        mbc.write_rom(0x0000, 0x1A);
+        assert_eq!(mbc.read_ram(0xBFFF), 0xFF);
+    }
+
+    #[test]
+    fn test_rumble() {
+        let rumble = Rc::new(Cell::new(false));
+        let mut mbc = mbc5(Some(Rc::clone(&rumble)));
+        mbc.write_rom(0x4000, 0x00);
+        mbc.write_ram(0xA000, 0x42);
+        mbc.write_rom(0x4000, 0x08);
+        assert!(rumble.get());
+        // The motor bit doesn't select a bank
+        assert_eq!(mbc.read_ram(0xA000), 0x42);
+        mbc.write_rom(0x4000, 0x00);
+        assert!(!rumble.get());
+    }
+}
@@ -1,9 +1,57 @@
 pub mod mbc0;
 pub mod mbc1;
+pub mod mbc2;
+pub mod mbc3;
+pub mod mbc5;
+
+use super::CartridgeError;
+use crate::savestate::{StateError, StateReader, StateWriter};
 
 pub trait Mbc {
     fn read_rom(&self, addr: u16) -> u8;
     fn write_rom(&mut self, addr: u16, value: u8);
     fn read_ram(&self, addr: u16) -> u8;
-    fn write_ram(&mut self, addr: u16, value: u8);
+    // True if the byte in RAM changed, so the .sav needs writing. Ignored writes and the RTC don't count.
+    fn write_ram(&mut self, addr: u16, value: u8) -> bool;
+
+    // Advance on-cartridge hardware (RTC) by `t_cycles`
+    fn step(&mut self, _t_cycles: u32) {}
+
+    fn has_battery(&self) -> bool;
+    // Battery-backed state in .sav layout: external RAM, then the RTC if there is one
+    fn save_data(&self) -> Vec<u8>;
+    fn load_save_data(&mut self, data: &[u8]);
+
+    // Bank registers, RAM and clock for save states. ROM isn't included.
+    fn save_state(&self, state: &mut StateWriter);
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError>;
+}
+
+// Copy as much of a save file as fits, so saves from other emulators with a different size still load
+pub fn load_ram(ram: &mut [u8], data: &[u8]) {
+    let len = ram.len().min(data.len());
+    ram[..len].copy_from_slice(&data[..len]);
+}
+
+// RAM size from the header byte at 0x0149
+pub fn num_of_ram_banks(code: u8) -> Result<usize, CartridgeError> {
+    match code {
+        0x00 => Ok(0),
+        0x02 => Ok(1),
+        0x03 => Ok(4),
+        0x04 => Ok(16),
+        0x05 => Ok(8),
+        _ => Err(CartridgeError::UnsupportedRamSize),
+    }
+}
+
+// Test ROM of `banks` 16 KiB banks, each starting with its number (low byte, then high byte)
+#[cfg(test)]
+pub fn numbered_rom(banks: usize) -> Vec<u8> {
+    let mut rom = vec![0; banks * 0x4000];
+    for (bank, chunk) in rom.chunks_mut(0x4000).enumerate() {
+        chunk[0] = bank as u8;
+        chunk[1] = (bank >> 8) as u8;
+    }
+    rom
 }
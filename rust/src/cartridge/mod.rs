@@ -1,22 +1,94 @@
+pub mod header;
 mod mbc;
-use crate::cartridge::mbc::{Mbc, mbc0::Mbc0, mbc1::Mbc1};
+use crate::RumbleSignal;
+use crate::cartridge::mbc::{Mbc, mbc0::Mbc0, mbc1::Mbc1, mbc2::Mbc2, mbc3::Mbc3, mbc5::Mbc5};
+use crate::savestate::{StateError, StateReader, StateWriter};
+use std::fmt;
+
+pub use header::{CartridgeHeader, CgbSupport};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CartridgeError {
+    TooShort,           // Ends before the header at 0x0100-0x014F
+    UnsupportedMapper,  // Cartridge type at 0x0147
+    UnsupportedRomSize, // ROM size at 0x0148
+    UnsupportedRamSize, // RAM size at 0x0149
+    BadHeaderChecksum,  // Checksum at 0x014D doesn't match 0x0134-0x014C
+    SizeMismatch,       // Smaller than the ROM size at 0x0148
+    BadBootRomSize,     // Boot ROM isn't 256 (DMG) or 2304 (CGB) bytes
+    Unavailable,        // The emulator thread exited before loading the cartridge
+}
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            CartridgeError::TooShort => "ROM is too short to contain a header",
+            CartridgeError::UnsupportedMapper => "Unsupported cartridge type",
+            CartridgeError::UnsupportedRomSize => "Unsupported ROM size",
+            CartridgeError::UnsupportedRamSize => "Unsupported RAM size",
+            CartridgeError::BadHeaderChecksum => "Header checksum mismatch",
+            CartridgeError::SizeMismatch => "ROM is smaller than its header says",
+            CartridgeError::BadBootRomSize => "Boot ROM must be 256 or 2304 bytes",
+            CartridgeError::Unavailable => "Emulator is not running",
+        };
+        f.write_str(message)
+    }
+}
+impl std::error::Error for CartridgeError {}
 
 pub struct Cartridge {
     pub mbc: Box<dyn Mbc>,
+    pub header: CartridgeHeader,
+    save_dirty: bool, // Battery-backed RAM changed since the last save_data
 }
 
 impl Cartridge {
-    pub fn new(rom_data: Vec<u8>) -> Self {
+    pub fn new(rom_data: Vec<u8>, rumble: RumbleSignal) -> Result<Self, CartridgeError> {
+        let header = Self::validate(&rom_data)?;
         let mbc_type = rom_data[0x0147];
         let mbc: Box<dyn Mbc> = match mbc_type {
-            0x00 => Box::new(Mbc0::new(rom_data)),        // MBC0
-            0x01 => Box::new(Mbc1::new(rom_data, false)), // MBC1
-            0x02 => Box::new(Mbc1::new(rom_data, false)), // MBC1 + RAM
-            0x03 => Box::new(Mbc1::new(rom_data, true)),  // MBC1 + RAM + BATTERY
-            0x08 | 0x09 => panic!("Unsupported MBC0 + RAM"),
-            _ => panic!("Unimplemented MBC type"),
+            0x00 => Box::new(Mbc0::new(rom_data, false, false)),         // MBC0
+            0x01 => Box::new(Mbc1::new(rom_data, false)?),               // MBC1
+            0x02 => Box::new(Mbc1::new(rom_data, false)?),               // MBC1 + RAM
+            0x03 => Box::new(Mbc1::new(rom_data, true)?),                // MBC1 + RAM + BATTERY
+            0x05 => Box::new(Mbc2::new(rom_data, false)),                // MBC2
+            0x06 => Box::new(Mbc2::new(rom_data, true)),                 // MBC2 + BATTERY
+            0x08 => Box::new(Mbc0::new(rom_data, true, false)),          // MBC0 + RAM
+            0x09 => Box::new(Mbc0::new(rom_data, true, true)),           // MBC0 + RAM + BATTERY
+            0x0F => Box::new(Mbc3::new(rom_data, true, true)?),          // MBC3 + TIMER + BATTERY
+            0x10 => Box::new(Mbc3::new(rom_data, true, true)?),          // MBC3 + TIMER + RAM + BATTERY
+            0x11 => Box::new(Mbc3::new(rom_data, false, false)?),        // MBC3
+            0x12 => Box::new(Mbc3::new(rom_data, false, false)?),        // MBC3 + RAM
+            0x13 => Box::new(Mbc3::new(rom_data, false, true)?),         // MBC3 + RAM + BATTERY
+            0x19 => Box::new(Mbc5::new(rom_data, false, None)?),         // MBC5
+            0x1A => Box::new(Mbc5::new(rom_data, false, None)?),         // MBC5 + RAM
+            0x1B => Box::new(Mbc5::new(rom_data, true, None)?),          // MBC5 + RAM + BATTERY
+            0x1C => Box::new(Mbc5::new(rom_data, false, Some(rumble))?), // MBC5 + RUMBLE
+            0x1D => Box::new(Mbc5::new(rom_data, false, Some(rumble))?), // MBC5 + RUMBLE + RAM
+            0x1E => Box::new(Mbc5::new(rom_data, true, Some(rumble))?),  // MBC5 + RUMBLE + RAM + BATTERY
+            _ => return Err(CartridgeError::UnsupportedMapper),
         };
-        Self { mbc }
+        Ok(Self {
+            mbc,
+            header,
+            save_dirty: false,
+        })
+    }
+
+    fn validate(rom_data: &[u8]) -> Result<CartridgeHeader, CartridgeError> {
+        let header = CartridgeHeader::parse(rom_data)?;
+        if !header.header_checksum_valid {
+            return Err(CartridgeError::BadHeaderChecksum);
+        }
+        // Mappers bank in 32 KiB << n, up to 8 MiB. The 0x52-0x54 sizes aren't powers of two, so bank numbers
+        // couldn't wrap at them, and no licensed cartridge used them.
+        if header.rom_size == 0 {
+            return Err(CartridgeError::UnsupportedRomSize);
+        }
+        // Overdumps are fine, bank numbers wrap at the header size
+        if rom_data.len() < header.rom_size as usize {
+            return Err(CartridgeError::SizeMismatch);
+        }
+        Ok(header)
     }
     pub fn read(&self, addr: u16) -> u8 {
         match addr {
@@ -28,8 +100,115 @@ impl Cartridge {
     pub fn write(&mut self, addr: u16, value: u8) {
         match addr {
             0x0000..=0x7FFF => self.mbc.write_rom(addr, value),
-            0xA000..=0xBFFF => self.mbc.write_ram(addr, value),
+            0xA000..=0xBFFF => {
+                if self.mbc.write_ram(addr, value) {
+                    self.save_dirty |= self.mbc.has_battery();
+                }
+            }
             _ => unreachable!(),
         }
     }
+    pub fn step(&mut self, t_cycles: u32) {
+        self.mbc.step(t_cycles);
+    }
+    // Contents of the .sav file, clears the dirty flag. Empty without a battery.
+    pub fn save_data(&mut self) -> Vec<u8> {
+        self.save_dirty = false;
+        self.mbc.save_data()
+    }
+    pub fn load_save_data(&mut self, data: &[u8]) {
+        self.mbc.load_save_data(data);
+    }
+    pub fn is_save_dirty(&self) -> bool {
+        self.save_dirty
+    }
+    pub fn save_state(&self, state: &mut StateWriter) {
+        self.mbc.save_state(state);
+    }
+    // Battery RAM is replaced too, so the .sav needs writing again
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.mbc.load_state(state)?;
+        self.save_dirty |= self.mbc.has_battery();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn rom(mbc_type: u8) -> Vec<u8> {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0147] = mbc_type;
+        fix_checksum(&mut rom);
+        rom
+    }
+
+    fn fix_checksum(rom: &mut [u8]) {
+        rom[0x014D] = header::header_checksum_of(rom);
+    }
+
+    fn load(rom: Vec<u8>) -> Result<Cartridge, CartridgeError> {
+        Cartridge::new(rom, Rc::new(Cell::new(false)))
+    }
+
+    #[test]
+    fn test_save_dirty() {
+        // MBC3 + TIMER + RAM + BATTERY, 32 KiB RAM
+        let mut rom = rom(0x10);
+        rom[0x0149] = 0x03;
+        fix_checksum(&mut rom);
+        let mut cartridge = load(rom).unwrap();
+
+        // RAM is still disabled
+        cartridge.write(0xA000, 0x12);
+        assert!(!cartridge.is_save_dirty());
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0xA000, 0x12);
+        assert!(cartridge.is_save_dirty());
+        cartridge.save_data();
+
+        // The same value again, then the RTC seconds register
+        cartridge.write(0xA000, 0x12);
+        cartridge.write(0x4000, 0x08);
+        cartridge.write(0xA000, 0x30);
+        assert!(!cartridge.is_save_dirty());
+    }
+
+    #[test]
+    fn test_valid_rom() {
+        assert!(load(rom(0x00)).is_ok());
+        assert!(load(rom(0x13)).is_ok());
+    }
+
+    #[test]
+    fn test_rejected_roms() {
+        assert_eq!(load(vec![0; 0x014F]).err(), Some(CartridgeError::TooShort));
+        assert_eq!(load(rom(0xFC)).err(), Some(CartridgeError::UnsupportedMapper));
+
+        let mut bad_checksum = rom(0x00);
+        bad_checksum[0x014D] ^= 0xFF;
+        assert_eq!(load(bad_checksum).err(), Some(CartridgeError::BadHeaderChecksum));
+
+        let mut bad_ram = rom(0x03);
+        bad_ram[0x0149] = 0x07;
+        fix_checksum(&mut bad_ram);
+        assert_eq!(load(bad_ram).err(), Some(CartridgeError::UnsupportedRamSize));
+
+        // 72 banks, listed in some docs but never used
+        let mut odd_size = rom(0x01);
+        odd_size[0x0148] = 0x52;
+        odd_size.resize(72 * 0x4000, 0);
+        fix_checksum(&mut odd_size);
+        assert_eq!(load(odd_size).err(), Some(CartridgeError::UnsupportedRomSize));
+
+        // Header claims 64 KiB
+        let mut truncated = rom(0x01);
+        truncated[0x0148] = 0x01;
+        fix_checksum(&mut truncated);
+        assert_eq!(load(truncated).err(), Some(CartridgeError::SizeMismatch));
+    }
 }
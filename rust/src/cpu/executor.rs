@@ -1,23 +1,23 @@
 use crate::cpu::Cpu;
 use crate::cpu::opcodes::{ByteDest, ByteLocation, ByteSource, JumpCondition, WordDest, WordLocation, WordSource};
-use crate::mmu::Bus;
+use crate::mmu::BusPort;
 
 impl Cpu {
     // Fetchers
-    pub(super) async fn fetch_byte(&mut self, bus: &mut Bus) -> u8 {
+    pub(super) async fn fetch_byte(&mut self, bus: &BusPort) -> u8 {
         let byte = bus.read(self.pc).await;
         self.pc = self.pc.wrapping_add(1);
         byte
     }
 
-    pub(super) async fn fetch_word(&mut self, bus: &mut Bus) -> u16 {
+    pub(super) async fn fetch_word(&mut self, bus: &BusPort) -> u16 {
         let lo = self.fetch_byte(bus).await;
         let hi = self.fetch_byte(bus).await;
         u16::from_le_bytes([lo, hi])
     }
 
     // Logic
-    pub(super) async fn run_jr(&mut self, bus: &mut Bus, cond: JumpCondition) {
+    pub(super) async fn run_jr(&mut self, bus: &BusPort, cond: JumpCondition) {
         // i8 offset
         let value = self.fetch_byte(bus).await as i8;
         if self.jump_condition_reached(cond) {
@@ -26,7 +26,7 @@ impl Cpu {
         }
     }
 
-    pub(super) async fn run_jp(&mut self, bus: &mut Bus, cond: JumpCondition) {
+    pub(super) async fn run_jp(&mut self, bus: &BusPort, cond: JumpCondition) {
         let value = self.fetch_word(bus).await;
         if self.jump_condition_reached(cond) {
             bus.tick().await; // +1 cycle
@@ -34,13 +34,13 @@ impl Cpu {
         }
     }
 
-    pub(super) async fn run_jp_hl(&mut self, _bus: &mut Bus) {
+    pub(super) async fn run_jp_hl(&mut self, _bus: &BusPort) {
         // +0 cycles
         let value = self.registers.get_hl();
         self.pc = value;
     }
 
-    pub(super) async fn run_ret(&mut self, bus: &mut Bus, cond: JumpCondition) {
+    pub(super) async fn run_ret(&mut self, bus: &BusPort, cond: JumpCondition) {
         match cond {
             JumpCondition::Always => {
                 // RET (Unconditional 0xC9)
@@ -68,7 +68,7 @@ impl Cpu {
         }
     }
 
-    pub(super) async fn run_reti(&mut self, bus: &mut Bus) {
+    pub(super) async fn run_reti(&mut self, bus: &BusPort) {
         let value = bus.read_u16(self.sp).await;
         self.sp = self.sp.wrapping_add(2);
         bus.tick().await; // +1 cycle
@@ -76,7 +76,7 @@ impl Cpu {
         self.ime = true;
     }
 
-    pub(super) async fn run_call(&mut self, bus: &mut Bus, cond: JumpCondition) {
+    pub(super) async fn run_call(&mut self, bus: &BusPort, cond: JumpCondition) {
         let value = self.fetch_word(bus).await;
         if self.jump_condition_reached(cond) {
             bus.tick().await; // +1 cycle
@@ -86,7 +86,7 @@ impl Cpu {
         }
     }
 
-    pub(super) async fn run_rst(&mut self, bus: &mut Bus, lsb: u8) {
+    pub(super) async fn run_rst(&mut self, bus: &BusPort, lsb: u8) {
         bus.tick().await; // +1 cycle
         self.sp = self.sp.wrapping_sub(2);
         bus.write_u16(self.sp, self.pc, false).await; // +2 cycles
@@ -94,17 +94,17 @@ impl Cpu {
         self.pc = u16::from_le_bytes([lsb, msb]);
     }
 
-    pub(super) async fn run_ld8(&mut self, bus: &mut Bus, dest: ByteDest, source: ByteSource) {
+    pub(super) async fn run_ld8(&mut self, bus: &BusPort, dest: ByteDest, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         self.write_byte_dest(bus, dest, value).await;
     }
 
-    pub(super) async fn run_ld16(&mut self, bus: &mut Bus, dest: WordDest, source: WordSource) {
+    pub(super) async fn run_ld16(&mut self, bus: &BusPort, dest: WordDest, source: WordSource) {
         let value = self.resolve_word_source(bus, source).await;
         self.write_word_dest(bus, dest, value).await;
     }
 
-    pub(super) async fn run_ldhl(&mut self, bus: &mut Bus, val: i8) {
+    pub(super) async fn run_ldhl(&mut self, bus: &BusPort, val: i8) {
         let val_unsigned = val as u8;
         let sp_plus_val = self.sp.wrapping_add_signed(val as i16);
         let half_carry = (self.sp & 0x000F) + (val_unsigned & 0x0F) as u16 > 0x000F;
@@ -114,20 +114,20 @@ impl Cpu {
         self.write_word_dest(bus, WordDest::HL, sp_plus_val).await;
     }
 
-    pub(super) async fn run_push(&mut self, bus: &mut Bus, source: WordSource) {
+    pub(super) async fn run_push(&mut self, bus: &BusPort, source: WordSource) {
         let value = self.resolve_word_source(bus, source).await;
         bus.tick().await; // Internal cycle
         self.sp = self.sp.wrapping_sub(2);
         bus.write_u16(self.sp, value, false).await;
     }
 
-    pub(super) async fn run_pop(&mut self, bus: &mut Bus, dest: WordDest) {
+    pub(super) async fn run_pop(&mut self, bus: &BusPort, dest: WordDest) {
         let value = bus.read_u16(self.sp).await;
         self.sp = self.sp.wrapping_add(2);
         self.write_word_dest(bus, dest, value).await;
     }
 
-    pub(super) async fn run_inc8(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_inc8(&mut self, bus: &BusPort, loc: ByteLocation) {
         let val = self.resolve_byte_source(bus, loc.into()).await;
         let new_val = val.wrapping_add(1);
         let half_carry = (val & 0x0F) == 0x0F;
@@ -136,7 +136,7 @@ impl Cpu {
         self.write_byte_dest(bus, loc.into(), new_val).await;
     }
 
-    pub(super) async fn run_dec8(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_dec8(&mut self, bus: &BusPort, loc: ByteLocation) {
         let val = self.resolve_byte_source(bus, loc.into()).await;
         let new_val = val.wrapping_sub(1);
         let half_carry = (val & 0x0F) == 0x00;
@@ -145,19 +145,19 @@ impl Cpu {
         self.write_byte_dest(bus, loc.into(), new_val).await;
     }
 
-    pub(super) async fn run_inc16(&mut self, bus: &mut Bus, loc: WordLocation) {
+    pub(super) async fn run_inc16(&mut self, bus: &BusPort, loc: WordLocation) {
         let value = self.resolve_word_source(bus, loc.into()).await;
         bus.tick().await; // +1 cycle
         self.write_word_dest(bus, loc.into(), value.wrapping_add(1)).await;
     }
 
-    pub(super) async fn run_dec16(&mut self, bus: &mut Bus, loc: WordLocation) {
+    pub(super) async fn run_dec16(&mut self, bus: &BusPort, loc: WordLocation) {
         let value = self.resolve_word_source(bus, loc.into()).await;
         bus.tick().await; // +1 cycle
         self.write_word_dest(bus, loc.into(), value.wrapping_sub(1)).await;
     }
 
-    pub(super) async fn run_addhl(&mut self, bus: &mut Bus, source: WordSource) {
+    pub(super) async fn run_addhl(&mut self, bus: &BusPort, source: WordSource) {
         let value = self.resolve_word_source(bus, source).await;
         bus.tick().await; // +1 cycle
         let hl = self.registers.get_hl();
@@ -168,7 +168,7 @@ impl Cpu {
         self.registers.set_hl(new_hl);
     }
 
-    pub(super) async fn run_addsp(&mut self, _bus: &mut Bus, val: i8) {
+    pub(super) async fn run_addsp(&mut self, _bus: &BusPort, val: i8) {
         let val_unsigned = val as u8;
         let half_carry = (self.sp & 0x000F) + (val_unsigned & 0x0F) as u16 > 0x000F;
         let carry = (self.sp & 0x00FF) + val_unsigned as u16 > 0xFF;
@@ -178,7 +178,7 @@ impl Cpu {
         self.sp = self.sp.wrapping_add_signed(val as i16);
     }
 
-    pub(super) async fn run_add(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_add(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let (new_a, carry) = a.overflowing_add(value);
@@ -188,7 +188,7 @@ impl Cpu {
         self.registers.a = new_a;
     }
 
-    pub(super) async fn run_adc(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_adc(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let c = if self.registers.f.carry { 1 } else { 0 };
@@ -201,7 +201,7 @@ impl Cpu {
         self.registers.a = new_byte_a;
     }
 
-    pub(super) async fn run_sub(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_sub(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let (new_a, carry) = a.overflowing_sub(value);
@@ -211,7 +211,7 @@ impl Cpu {
         self.registers.a = new_a;
     }
 
-    pub(super) async fn run_sbc(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_sbc(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let c = if self.registers.f.carry { 1 } else { 0 };
@@ -225,7 +225,7 @@ impl Cpu {
         self.registers.a = new_byte_a;
     }
 
-    pub(super) async fn run_and(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_and(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let new_a = a & value;
@@ -234,7 +234,7 @@ impl Cpu {
         self.registers.a = new_a;
     }
 
-    pub(super) async fn run_xor(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_xor(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let new_a = a ^ value;
@@ -243,7 +243,7 @@ impl Cpu {
         self.registers.a = new_a;
     }
 
-    pub(super) async fn run_or(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_or(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let new_a = a | value;
@@ -252,7 +252,7 @@ impl Cpu {
         self.registers.a = new_a;
     }
 
-    pub(super) async fn run_cp(&mut self, bus: &mut Bus, source: ByteSource) {
+    pub(super) async fn run_cp(&mut self, bus: &BusPort, source: ByteSource) {
         let value = self.resolve_byte_source(bus, source).await;
         let a = self.registers.a;
         let (new_a, carry) = a.overflowing_sub(value);
@@ -297,7 +297,7 @@ impl Cpu {
         self.update_flags(None, Some(false), Some(false), Some(!self.registers.f.carry));
     }
 
-    pub(super) async fn run_rlc(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_rlc(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b7 = old_val >> 7;
             let new_val = (old_val << 1) | b7;
@@ -306,11 +306,11 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_rlca(&mut self, bus: &mut Bus) {
+    pub(super) async fn run_rlca(&mut self, bus: &BusPort) {
         self.run_rlc(bus, ByteLocation::A).await;
         self.update_flags(Some(false), None, None, None); // Accumulator counterpart sets z = 0
     }
-    pub(super) async fn run_rrc(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_rrc(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b0 = old_val & 0b0000_0001;
             let new_val = (old_val >> 1) | (b0 << 7);
@@ -319,11 +319,11 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_rrca(&mut self, bus: &mut Bus) {
+    pub(super) async fn run_rrca(&mut self, bus: &BusPort) {
         self.run_rrc(bus, ByteLocation::A).await;
         self.update_flags(Some(false), None, None, None); // Accumulator counterpart sets z = 0
     }
-    pub(super) async fn run_rl(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_rl(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b7 = old_val >> 7;
             let carry = if cpu.registers.f.carry { 1 } else { 0 };
@@ -333,11 +333,11 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_rla(&mut self, bus: &mut Bus) {
+    pub(super) async fn run_rla(&mut self, bus: &BusPort) {
         self.run_rl(bus, ByteLocation::A).await;
         self.update_flags(Some(false), None, None, None); // Accumulator counterpart sets z = 0
     }
-    pub(super) async fn run_rr(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_rr(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b0 = old_val & 0b0000_0001;
             let carry = if cpu.registers.f.carry { 1 } else { 0 };
@@ -347,11 +347,11 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_rra(&mut self, bus: &mut Bus) {
+    pub(super) async fn run_rra(&mut self, bus: &BusPort) {
         self.run_rr(bus, ByteLocation::A).await;
         self.update_flags(Some(false), None, None, None); // Accumulator counterpart sets z = 0
     }
-    pub(super) async fn run_sla(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_sla(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b7 = old_val >> 7;
             let new_val = old_val << 1;
@@ -360,7 +360,7 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_sra(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_sra(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b0 = old_val & 0b0000_0001;
             let b7 = old_val >> 7;
@@ -370,7 +370,7 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_swap(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_swap(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let old_lo = old_val & 0b0000_1111;
             let old_hi = (old_val & 0b1111_0000) >> 4;
@@ -380,7 +380,7 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_srl(&mut self, bus: &mut Bus, loc: ByteLocation) {
+    pub(super) async fn run_srl(&mut self, bus: &BusPort, loc: ByteLocation) {
         self.modify_loc(bus, loc, |cpu, old_val| {
             let b0 = old_val & 0b0000_0001;
             let new_val = old_val >> 1;
@@ -389,7 +389,7 @@ impl Cpu {
         })
         .await;
     }
-    pub(super) async fn run_bit(&mut self, bus: &mut Bus, loc: ByteLocation, bit: u8) {
+    pub(super) async fn run_bit(&mut self, bus: &BusPort, loc: ByteLocation, bit: u8) {
         // No write, don't use modify_loc
         let val = match loc {
             ByteLocation::IndHL => bus.read(self.registers.get_hl()).await, // +1 cycle
@@ -401,19 +401,17 @@ impl Cpu {
         self.registers.f.subtract = false;
         self.registers.f.half_carry = true;
     }
-    pub(super) async fn run_res(&mut self, bus: &mut Bus, loc: ByteLocation, bit: u8) {
+    pub(super) async fn run_res(&mut self, bus: &BusPort, loc: ByteLocation, bit: u8) {
         self.modify_loc(bus, loc, |_cpu, old_val| {
             let target_bit = 0b0000_0001 << bit;
-            let new_val = old_val & !target_bit;
-            new_val
+            old_val & !target_bit
         })
         .await;
     }
-    pub(super) async fn run_set(&mut self, bus: &mut Bus, loc: ByteLocation, bit: u8) {
+    pub(super) async fn run_set(&mut self, bus: &BusPort, loc: ByteLocation, bit: u8) {
         self.modify_loc(bus, loc, |_cpu, old_val| {
             let target_bit = 0b0000_0001 << bit;
-            let new_val = old_val | target_bit;
-            new_val
+            old_val | target_bit
         })
         .await;
     }
@@ -433,7 +431,7 @@ impl Cpu {
     }
 
     // r/w
-    async fn resolve_byte_source(&mut self, bus: &mut Bus, source: ByteSource) -> u8 {
+    async fn resolve_byte_source(&mut self, bus: &BusPort, source: ByteSource) -> u8 {
         match source {
             ByteSource::A => self.registers.a,
             ByteSource::B => self.registers.b,
@@ -462,7 +460,7 @@ impl Cpu {
         }
     }
 
-    async fn write_byte_dest(&mut self, bus: &mut Bus, dest: ByteDest, value: u8) {
+    async fn write_byte_dest(&mut self, bus: &BusPort, dest: ByteDest, value: u8) {
         match dest {
             ByteDest::A => self.registers.a = value,
             ByteDest::B => self.registers.b = value,
@@ -490,7 +488,7 @@ impl Cpu {
         }
     }
 
-    async fn resolve_word_source(&mut self, _bus: &mut Bus, source: WordSource) -> u16 {
+    async fn resolve_word_source(&mut self, _bus: &BusPort, source: WordSource) -> u16 {
         match source {
             WordSource::AF => self.registers.get_af(),
             WordSource::BC => self.registers.get_bc(),
@@ -501,7 +499,7 @@ impl Cpu {
         }
     }
 
-    async fn write_word_dest(&mut self, bus: &mut Bus, dest: WordDest, value: u16) {
+    async fn write_word_dest(&mut self, bus: &BusPort, dest: WordDest, value: u16) {
         match dest {
             WordDest::AF => self.registers.set_af(value & 0xFFF0),
             WordDest::BC => self.registers.set_bc(value),
@@ -550,7 +548,7 @@ impl Cpu {
         }
     }
 
-    async fn modify_loc<F>(&mut self, bus: &mut Bus, loc: ByteLocation, op: F)
+    async fn modify_loc<F>(&mut self, bus: &BusPort, loc: ByteLocation, op: F)
     where
         F: FnOnce(&mut Self, u8) -> u8,
     {
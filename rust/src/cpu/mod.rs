@@ -4,7 +4,8 @@ mod registers;
 
 use crate::cpu::opcodes::{ByteDest, ByteSource, JumpCondition, WordDest, WordLocation, WordSource};
 use crate::cpu::registers::Registers;
-use crate::mmu::Bus;
+use crate::mmu::BusPort;
+use crate::savestate::{StateError, StateReader, StateWriter};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 
@@ -15,15 +16,16 @@ pub struct Logger {
 }
 
 impl Logger {
-    pub fn new(filename: &str, max_lines: u64) -> Self {
-        let file = File::create(filename).expect("Unable to create log file");
-        Self {
+    pub fn new(filename: &str, max_lines: u64) -> std::io::Result<Self> {
+        let file = File::create(filename)?;
+        Ok(Self {
             writer: BufWriter::new(file),
             count: 0,
             max_lines,
-        }
+        })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn log(&mut self, pc: u16, sp: u16, a: u8, f: u8, b: u8, c: u8, d: u8, e: u8, h: u8, l: u8) {
         if self.count < self.max_lines {
             // A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100
@@ -37,7 +39,7 @@ impl Logger {
         } else if self.count == self.max_lines {
             self.writer.flush().unwrap();
             self.count += 1;
-            println!("Reached log limit. Stopping log.");
+            eprintln!("Reached log limit. Stopping log.");
         }
     }
 }
@@ -50,7 +52,14 @@ pub struct Cpu {
     pub ime_countdown: u8,
     pub halted: bool,
     pub halt_bug_active: bool,
-    logger: Logger,
+    pub breakpoint: bool,       // Set by LD B, B, for test ROMs. Not hardware state.
+    pub logger: Option<Logger>, // Off unless asked for, see DancyHandle::log_cpu
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Cpu {
@@ -64,11 +73,52 @@ impl Cpu {
             ime_countdown: 0,
             halted: false,
             halt_bug_active: false,
-            logger: Logger::new("log.txt", 2000000),
+            breakpoint: false,
+            logger: None,
+        }
+    }
+
+    // Start at 0x0000 with a boot ROM mapped in
+    pub fn power_on() -> Self {
+        Cpu {
+            registers: Registers::power_on(),
+            pc: 0x0000,
+            sp: 0x0000,
+            ..Self::new()
         }
     }
 
-    async fn handle_interrupt(&mut self, bus: &mut Bus, pending: u8) {
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u16(self.registers.get_af());
+        state.u16(self.registers.get_bc());
+        state.u16(self.registers.get_de());
+        state.u16(self.registers.get_hl());
+        state.u16(self.pc);
+        state.u16(self.sp);
+        state.bool(self.ime);
+        state.u8(self.ime_countdown);
+        state.bool(self.halted);
+        state.bool(self.halt_bug_active);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.registers.set_af(state.u16()?);
+        self.registers.set_bc(state.u16()?);
+        self.registers.set_de(state.u16()?);
+        self.registers.set_hl(state.u16()?);
+        self.pc = state.u16()?;
+        self.sp = state.u16()?;
+        self.ime = state.bool()?;
+        self.ime_countdown = state.u8()?;
+        self.halted = state.bool()?;
+        self.halt_bug_active = state.bool()?;
+        if self.ime_countdown > 2 {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+
+    async fn handle_interrupt(&mut self, bus: &BusPort, pending: u8) {
         bus.tick().await;
         bus.tick().await;
         self.ime = false;
@@ -78,14 +128,14 @@ impl Cpu {
         for bit in 0..5 {
             let target = 0b1 << bit;
             if pending & target != 0 {
-                bus.interrupt_flag &= !target;
+                bus.acknowledge_interrupt(target);
                 self.pc = u16::from_le_bytes([0x40 + 0x08 * bit, 0x00]);
                 return;
             }
         }
     }
 
-    pub async fn step(&mut self, bus: &mut Bus) {
+    pub async fn step(&mut self, bus: &BusPort) {
         // For EI, set IME to true AFTER the NEXT cycle.
         if self.ime_countdown > 0 {
             self.ime_countdown -= 1;
@@ -96,16 +146,14 @@ impl Cpu {
 
         // If IME is set, CPU wakes up
         if !self.halted {
-            let pending = bus.interrupt_enable & bus.interrupt_flag & 0x1F;
-            if pending != 0 {
-                if self.ime {
-                    self.handle_interrupt(bus, pending).await;
-                }
+            let pending = bus.pending_interrupts();
+            if pending != 0 && self.ime {
+                self.handle_interrupt(bus, pending).await;
             }
         } else {
             // If halted
             bus.tick().await; // +1 cycle
-            let pending = bus.interrupt_enable & bus.interrupt_flag & 0x1F; // Recalculate after the await
+            let pending = bus.pending_interrupts(); // Recalculate after the await
             if pending != 0 {
                 self.halted = false;
                 if self.ime {
@@ -117,18 +165,20 @@ impl Cpu {
             }
         }
 
-        self.logger.log(
-            self.pc,
-            self.sp,
-            self.registers.a,
-            self.registers.f.into(),
-            self.registers.b,
-            self.registers.c,
-            self.registers.d,
-            self.registers.e,
-            self.registers.h,
-            self.registers.l,
-        );
+        if let Some(logger) = &mut self.logger {
+            logger.log(
+                self.pc,
+                self.sp,
+                self.registers.a,
+                self.registers.f.into(),
+                self.registers.b,
+                self.registers.c,
+                self.registers.d,
+                self.registers.e,
+                self.registers.h,
+                self.registers.l,
+            );
+        }
 
         let opcode = bus.read(self.pc).await;
         self.pc = self.pc.wrapping_add(1);
@@ -149,7 +199,7 @@ impl Cpu {
             0x10 => {
                 // Minimal implementation. Commercial DMG games don't use STOP because it's very buggy.
                 // https://gbdev.io/pandocs/Reducing_Power_Consumption.html#the-bizarre-case-of-the-game-boy-stop-instruction-before-even-considering-timing
-                let pending = bus.interrupt_enable & bus.interrupt_flag & 0x1F;
+                let pending = bus.pending_interrupts();
                 if pending == 0 {
                     let _discarded_byte = self.fetch_byte(bus).await;
                 }
@@ -158,7 +208,7 @@ impl Cpu {
 
             // HALT
             0x76 => {
-                let pending = bus.interrupt_enable & bus.interrupt_flag & 0x1F;
+                let pending = bus.pending_interrupts();
                 if !self.ime && pending != 0 {
                     // Does not enter HALT mode
                     self.halt_bug_active = true;
@@ -178,6 +228,10 @@ impl Cpu {
 
             // LD r, r'
             0x40..=0x7F => {
+                // LD B, B does nothing, mooneye's test ROMs run it when they're done
+                if opcode == 0x40 {
+                    self.breakpoint = true;
+                }
                 let dest: ByteDest = self.decode_bits_to_location((opcode >> 3) & 0b0000_0111).into();
                 let src: ByteSource = self.decode_bits_to_location(opcode & 0b0000_0111).into();
                 self.run_ld8(bus, dest, src).await;
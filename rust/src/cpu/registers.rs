@@ -60,6 +60,20 @@ impl Registers {
         }
     }
 
+    // Everything cleared, the boot ROM sets them up
+    pub fn power_on() -> Self {
+        Self {
+            a: 0x00,
+            f: 0x00.into(),
+            b: 0x00,
+            c: 0x00,
+            d: 0x00,
+            e: 0x00,
+            h: 0x00,
+            l: 0x00,
+        }
+    }
+
     pub fn get_af(&self) -> u16 {
         let flags: u8 = self.f.into();
         u16::from_le_bytes([flags, self.a])
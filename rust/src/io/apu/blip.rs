@@ -0,0 +1,76 @@
+use std::f64::consts::PI;
+
+const PHASES: usize = 32; // Sub-sample positions a step can start at
+const TAPS: usize = 16; // Output samples touched by one step, adds TAPS / 2 samples of latency
+
+// Band-limited resampler. The signal goes in as amplitude steps at T-cycle resolution and every step
+// is drawn as a windowed-sinc step, so nothing above the output Nyquist frequency aliases back down.
+pub struct BlipBuffer {
+    kernel: Vec<[f32; TAPS]>,
+    samples_per_clock: f64,
+    time: f64,        // Position of the next step, in output samples from the front of `deltas`
+    deltas: Vec<f32>, // Differences between consecutive output samples
+    integrator: f32,  // Amplitude of the last output sample
+}
+impl BlipBuffer {
+    pub fn new(clock_rate: u32, sample_rate: u32) -> Self {
+        let mut kernel = vec![[0.0; TAPS]; PHASES];
+        // Cut off a little below Nyquist to leave room for the window's transition band
+        let cutoff = 0.45;
+        for (phase, taps) in kernel.iter_mut().enumerate() {
+            let offset = phase as f64 / PHASES as f64;
+            for (i, tap) in taps.iter_mut().enumerate() {
+                let x = i as f64 - (TAPS / 2) as f64 + 1.0 - offset;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (2.0 * PI * cutoff * x).sin() / (PI * x * 2.0 * cutoff)
+                };
+                // Blackman window over the kernel width
+                let n = (x + TAPS as f64 / 2.0) / TAPS as f64;
+                let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+                *tap = (sinc * window) as f32;
+            }
+            // Every step must add up to exactly its height
+            let sum: f32 = taps.iter().sum();
+            taps.iter_mut().for_each(|tap| *tap /= sum);
+        }
+
+        Self {
+            kernel,
+            samples_per_clock: sample_rate as f64 / clock_rate as f64,
+            time: 0.0,
+            deltas: vec![0.0; TAPS],
+            integrator: 0.0,
+        }
+    }
+
+    // Change the amplitude by `delta` at the current time
+    pub fn add_delta(&mut self, delta: f32) {
+        let index = self.time as usize;
+        let phase = ((self.time - index as f64) * PHASES as f64) as usize;
+        if self.deltas.len() < index + TAPS {
+            self.deltas.resize(index + TAPS, 0.0);
+        }
+        for (slot, tap) in self.deltas[index..index + TAPS].iter_mut().zip(&self.kernel[phase]) {
+            *slot += delta * tap;
+        }
+    }
+
+    pub fn advance(&mut self, clocks: u32) {
+        self.time += clocks as f64 * self.samples_per_clock;
+    }
+
+    // Output samples that no future step can reach anymore
+    pub fn samples_available(&self) -> usize {
+        self.time as usize
+    }
+
+    pub fn read_sample(&mut self) -> f32 {
+        debug_assert!(self.samples_available() > 0);
+        self.integrator += self.deltas.remove(0);
+        self.deltas.push(0.0);
+        self.time -= 1.0;
+        self.integrator
+    }
+}
@@ -0,0 +1,79 @@
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// Volume envelope, clocked by the frame sequencer at 64 Hz
+pub struct Envelope {
+    initial_volume: u8,
+    increase: bool,
+    period: u8,
+    volume: u8,
+    timer: u8,
+}
+impl Envelope {
+    pub fn new() -> Self {
+        Self {
+            initial_volume: 0,
+            increase: false,
+            period: 0,
+            volume: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    // NRx2
+    pub fn read(&self) -> u8 {
+        (self.initial_volume << 4) | ((self.increase as u8) << 3) | self.period
+    }
+
+    pub fn write(&mut self, value: u8) {
+        self.initial_volume = value >> 4;
+        self.increase = value & 0b0000_1000 != 0;
+        self.period = value & 0b0000_0111;
+    }
+
+    // Upper 5 bits of NRx2 all zero turns the DAC off
+    pub fn dac_enabled(&self) -> bool {
+        self.initial_volume != 0 || self.increase
+    }
+
+    pub fn trigger(&mut self) {
+        self.volume = self.initial_volume;
+        self.timer = self.period;
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.read());
+        state.u8(self.volume);
+        state.u8(self.timer);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.write(state.u8()?);
+        self.volume = state.u8()?;
+        self.timer = state.u8()?;
+        if self.volume > 15 || self.timer > 7 {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+
+    pub fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+        if self.timer > 0 {
+            self.timer -= 1;
+        }
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.increase && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increase && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
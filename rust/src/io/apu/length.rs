@@ -0,0 +1,73 @@
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// Silences the channel after `max - NRx1` frame sequencer length clocks (256 Hz)
+pub struct LengthCounter {
+    max: u16, // 64, or 256 for the wave channel
+    counter: u16,
+    enabled: bool,
+}
+impl LengthCounter {
+    pub fn new(max: u16) -> Self {
+        Self {
+            max,
+            counter: 0,
+            enabled: false,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // NRx1
+    pub fn load(&mut self, value: u8) {
+        self.counter = self.max - (value as u16 & (self.max - 1));
+    }
+
+    // Returns true when the counter runs out and the channel should be disabled
+    pub fn clock(&mut self) -> bool {
+        if self.enabled && self.counter > 0 {
+            self.counter -= 1;
+            return self.counter == 0;
+        }
+        false
+    }
+
+    // NRx4. When the next frame sequencer step doesn't clock length, enabling length clocks it once extra
+    // and a trigger reloading an empty counter loads one less.
+    // Returns true when the extra clock empties the counter and the channel should be disabled.
+    pub fn write_control(&mut self, enable: bool, trigger: bool, next_step_clocks_length: bool) -> bool {
+        let extra_clock = !next_step_clocks_length;
+        let was_enabled = self.enabled;
+        self.enabled = enable;
+
+        let mut disable = false;
+        if extra_clock && !was_enabled && enable && self.counter > 0 {
+            self.counter -= 1;
+            disable = self.counter == 0 && !trigger;
+        }
+        if trigger && self.counter == 0 {
+            self.counter = if extra_clock && enable { self.max - 1 } else { self.max };
+        }
+        disable
+    }
+
+    // APU power off clears the enable bit. On DMG the counter itself survives.
+    pub fn power_off(&mut self) {
+        self.enabled = false;
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u16(self.counter);
+        state.bool(self.enabled);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.counter = state.u16()?;
+        self.enabled = state.bool()?;
+        if self.counter > self.max {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use super::blip::BlipBuffer;
+use crate::SharedAudioBuffer;
+use crate::mmu::CLOCK_RATE;
+use std::rc::Rc;
+
+// Stereo output stage: resamples the mixed signal and runs it through the high-pass filter
+// the DMG uses to remove DC offset.
+pub struct Mixer {
+    left: BlipBuffer,
+    right: BlipBuffer,
+    last: (f32, f32),
+    capacitor: (f32, f32),
+    charge_factor: f32,
+    sample_rate: u32,
+    output: SharedAudioBuffer,
+}
+impl Mixer {
+    pub fn new(output: SharedAudioBuffer) -> Self {
+        let sample_rate = output.borrow().sample_rate;
+        Self {
+            left: BlipBuffer::new(CLOCK_RATE, sample_rate),
+            right: BlipBuffer::new(CLOCK_RATE, sample_rate),
+            last: (0.0, 0.0),
+            capacitor: (0.0, 0.0),
+            charge_factor: 0.999958f64.powf(CLOCK_RATE as f64 / sample_rate as f64) as f32,
+            sample_rate,
+            output,
+        }
+    }
+
+    // `left` and `right` are the analog levels (-1.0 to 1.0) for the next `t_cycles`
+    pub fn step(&mut self, left: f32, right: f32, t_cycles: u32) {
+        if left != self.last.0 {
+            self.left.add_delta(left - self.last.0);
+        }
+        if right != self.last.1 {
+            self.right.add_delta(right - self.last.1);
+        }
+        self.last = (left, right);
+        self.left.advance(t_cycles);
+        self.right.advance(t_cycles);
+
+        if self.left.samples_available() == 0 {
+            return;
+        }
+        if self.output.borrow().sample_rate != self.sample_rate {
+            // Output rate changed, start over
+            *self = Self::new(Rc::clone(&self.output));
+            return;
+        }
+        let mut output = self.output.borrow_mut();
+
+        // Both sides advance in lockstep, so they have the same samples ready
+        while self.left.samples_available() > 0 {
+            let left = Self::high_pass(&mut self.capacitor.0, self.charge_factor, self.left.read_sample());
+            let right = Self::high_pass(&mut self.capacitor.1, self.charge_factor, self.right.read_sample());
+            output.samples.push(Self::to_i16(left));
+            output.samples.push(Self::to_i16(right));
+            output.produced += 1;
+        }
+
+        // Keep one second when nobody drains the buffer
+        let max_buffered = self.sample_rate as usize * 2;
+        if output.samples.len() > max_buffered {
+            let excess = output.samples.len() - max_buffered;
+            output.samples.drain(..excess);
+        }
+    }
+
+    // Drop buffered output and filter state, e.g. after jumping to a save state
+    pub fn reset(&mut self) {
+        *self = Self::new(Rc::clone(&self.output));
+    }
+
+    fn high_pass(capacitor: &mut f32, charge_factor: f32, input: f32) -> f32 {
+        let output = input - *capacitor;
+        *capacitor = input - output * charge_factor;
+        output
+    }
+
+    fn to_i16(sample: f32) -> i16 {
+        (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+    }
+}
@@ -1,15 +1,456 @@
-pub struct Apu;
+mod blip;
+mod envelope;
+mod length;
+mod mixer;
+mod noise;
+mod pulse;
+mod wave;
 
-impl Apu {
+use mixer::Mixer;
+use noise::Noise;
+use pulse::Pulse;
+use wave::Wave;
+
+use crate::SharedAudioBuffer;
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// Output rates the frontend can ask for, others are clamped to this range
+pub const SAMPLE_RATES: std::ops::RangeInclusive<u32> = 8000..=192000;
+
+// Mixed output, drained by the frontend
+pub struct AudioBuffer {
+    pub samples: Vec<i16>, // Interleaved L/R
+    pub sample_rate: u32,
+    pub produced: u64, // Stereo samples mixed since power on, unaffected by draining
+}
+impl Default for AudioBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl AudioBuffer {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            samples: Vec::new(),
+            sample_rate: 48000,
+            produced: 0,
+        }
+    }
+}
+
+pub struct Apu {
+    enabled: bool, // NR52.7
+    r_nr50: u8,    // 0xFF24
+    r_nr51: u8,    // 0xFF25
+
+    ch1: Pulse,
+    ch2: Pulse,
+    ch3: Wave,
+    ch4: Noise,
+
+    frame_step: u8, // Next frame sequencer step (0 to 7)
+
+    mixer: Mixer,
+}
+
+impl Apu {
+    pub fn new(audio_buffer: SharedAudioBuffer) -> Self {
+        let mut apu = Self::power_on(audio_buffer);
+        // Left behind by the boot ROM
+        apu.write(0xFF26, 0x80);
+        apu.write(0xFF24, 0x77);
+        apu.write(0xFF25, 0xF3);
+        apu.write(0xFF11, 0x80);
+        apu.write(0xFF12, 0xF3);
+        apu
+    }
+
+    // Powered off until the boot ROM writes NR52
+    pub fn power_on(audio_buffer: SharedAudioBuffer) -> Self {
+        Self {
+            enabled: false,
+            r_nr50: 0x00,
+            r_nr51: 0x00,
+
+            ch1: Pulse::new(true),
+            ch2: Pulse::new(false),
+            ch3: Wave::new(),
+            ch4: Noise::new(),
+
+            frame_step: 0,
+
+            mixer: Mixer::new(audio_buffer),
+        }
+    }
+
+    pub fn step(&mut self, t_cycles: u32) {
+        if self.enabled {
+            self.ch1.step(t_cycles);
+            self.ch2.step(t_cycles);
+            self.ch3.step(t_cycles);
+            self.ch4.step(t_cycles);
+        }
+
+        let (left, right) = self.mix();
+        self.mixer.step(left, right, t_cycles);
+    }
+
+    // Analog level of both terminals, -1.0 to 1.0
+    fn mix(&self) -> (f32, f32) {
+        let channels = [
+            (self.ch1.dac_enabled(), self.ch1.output()),
+            (self.ch2.dac_enabled(), self.ch2.output()),
+            (self.ch3.dac_enabled(), self.ch3.output()),
+            (self.ch4.dac_enabled(), self.ch4.output()),
+        ];
+        let (mut left, mut right) = (0.0, 0.0);
+        for (i, (dac_enabled, output)) in channels.into_iter().enumerate() {
+            if !dac_enabled {
+                continue; // Disabled DACs output 0 instead of their lowest level
+            }
+            // DAC maps 0 to 15 onto 1.0 to -1.0
+            let analog = 1.0 - output as f32 / 7.5;
+            // NR51: bits 4-7 route channels 1-4 to the left, bits 0-3 to the right
+            if self.r_nr51 & (0b0001_0000 << i) != 0 {
+                left += analog;
+            }
+            if self.r_nr51 & (0b0000_0001 << i) != 0 {
+                right += analog;
+            }
+        }
+        // NR50: master volume 0-7 per side (bits 4-6 left, 0-2 right), never fully silent
+        let left_volume = (((self.r_nr50 >> 4) & 0b111) + 1) as f32 / 8.0;
+        let right_volume = ((self.r_nr50 & 0b111) + 1) as f32 / 8.0;
+        (left / 4.0 * left_volume, right / 4.0 * right_volume)
+    }
+
+    // Clocked at 512 Hz by the timer's DIV falling edge.
+    // Step:   0   1   2   3   4   5   6   7
+    // Length  x       x       x       x
+    // Sweep           x               x
+    // Volume                              x
+    pub fn clock_frame_sequencer(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        if self.frame_step.is_multiple_of(2) {
+            self.ch1.clock_length();
+            self.ch2.clock_length();
+            self.ch3.clock_length();
+            self.ch4.clock_length();
+        }
+        if self.frame_step == 2 || self.frame_step == 6 {
+            self.ch1.clock_sweep();
+        }
+        if self.frame_step == 7 {
+            self.ch1.clock_envelope();
+            self.ch2.clock_envelope();
+            self.ch4.clock_envelope();
+        }
+        self.frame_step = (self.frame_step + 1) & 0b111;
+    }
+
+    fn next_step_clocks_length(&self) -> bool {
+        self.frame_step.is_multiple_of(2)
     }
 
     pub fn read(&self, addr: u16) -> u8 {
-        0xFF // TODO
+        match addr {
+            0xFF10..=0xFF14 => self.ch1.read(addr - 0xFF10),
+            0xFF15..=0xFF19 => self.ch2.read(addr - 0xFF15),
+            0xFF1A..=0xFF1E => self.ch3.read(addr - 0xFF1A),
+            0xFF1F..=0xFF23 => self.ch4.read(addr - 0xFF1F),
+            0xFF24 => self.r_nr50,
+            0xFF25 => self.r_nr51,
+            0xFF26 => {
+                ((self.enabled as u8) << 7)
+                    | 0x70 // Unused bits read as 1
+                    | ((self.ch4.enabled() as u8) << 3)
+                    | ((self.ch3.enabled() as u8) << 2)
+                    | ((self.ch2.enabled() as u8) << 1)
+                    | self.ch1.enabled() as u8
+            }
+            0xFF30..=0xFF3F => self.ch3.read_wave_ram(addr),
+            _ => unreachable!(),
+        }
     }
-    
+
     pub fn write(&mut self, addr: u16, value: u8) {
-        // TODO
+        if addr == 0xFF26 {
+            let enable = value & 0b1000_0000 != 0;
+            if self.enabled && !enable {
+                self.power_off();
+            } else if !self.enabled && enable {
+                self.frame_step = 0;
+            }
+            self.enabled = enable;
+            return;
+        }
+        if !self.enabled {
+            // Only length counters (DMG only, CGB clears them too) and wave RAM can be written while the APU is off
+            match addr {
+                0xFF11 => self.ch1.length.load(value),
+                0xFF16 => self.ch2.length.load(value),
+                0xFF1B => self.ch3.length.load(value),
+                0xFF20 => self.ch4.length.load(value),
+                0xFF30..=0xFF3F => self.ch3.write_wave_ram(addr, value),
+                _ => {}
+            }
+            return;
+        }
+
+        let next_step_clocks_length = self.next_step_clocks_length();
+        match addr {
+            0xFF10..=0xFF14 => self.ch1.write(addr - 0xFF10, value, next_step_clocks_length),
+            0xFF15..=0xFF19 => self.ch2.write(addr - 0xFF15, value, next_step_clocks_length),
+            0xFF1A..=0xFF1E => self.ch3.write(addr - 0xFF1A, value, next_step_clocks_length),
+            0xFF1F..=0xFF23 => self.ch4.write(addr - 0xFF1F, value, next_step_clocks_length),
+            0xFF24 => self.r_nr50 = value,
+            0xFF25 => self.r_nr51 = value,
+            0xFF30..=0xFF3F => self.ch3.write_wave_ram(addr, value),
+            _ => unreachable!(),
+        }
+    }
+
+    // Clears every register except wave RAM
+    fn power_off(&mut self) {
+        self.r_nr50 = 0;
+        self.r_nr51 = 0;
+        self.ch1.power_off();
+        self.ch2.power_off();
+        self.ch3.power_off();
+        self.ch4.power_off();
+    }
+
+    // The resampler isn't saved, it starts over from silence
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.bool(self.enabled);
+        state.u8(self.r_nr50);
+        state.u8(self.r_nr51);
+        self.ch1.save_state(state);
+        self.ch2.save_state(state);
+        self.ch3.save_state(state);
+        self.ch4.save_state(state);
+        state.u8(self.frame_step);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.enabled = state.bool()?;
+        self.r_nr50 = state.u8()?;
+        self.r_nr51 = state.u8()?;
+        self.ch1.load_state(state)?;
+        self.ch2.load_state(state)?;
+        self.ch3.load_state(state)?;
+        self.ch4.load_state(state)?;
+        self.frame_step = state.u8()?;
+        if self.frame_step > 7 {
+            return Err(StateError::Corrupt);
+        }
+        self.mixer.reset();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn new_apu() -> Apu {
+        Apu::new(Rc::new(RefCell::new(AudioBuffer::new())))
+    }
+
+    fn run_steps(apu: &mut Apu, steps: u32) {
+        for _ in 0..steps {
+            apu.clock_frame_sequencer();
+        }
+    }
+
+    #[test]
+    fn test_register_read_masks() {
+        let mut apu = new_apu();
+        for addr in 0xFF10..=0xFF19 {
+            apu.write(addr, 0x00);
+        }
+        let masks = [0x80, 0x3F, 0x00, 0xFF, 0xBF, 0xFF, 0x3F, 0x00, 0xFF, 0xBF];
+        for (addr, mask) in (0xFF10..=0xFF19).zip(masks) {
+            assert_eq!(apu.read(addr), mask, "{:04X}", addr);
+        }
+    }
+
+    #[test]
+    fn test_length_counter_disables_channel() {
+        let mut apu = new_apu();
+        apu.write(0xFF17, 0xF0); // DAC on
+        apu.write(0xFF16, 62); // Length 2
+        apu.write(0xFF19, 0b1100_0000); // Trigger, length enabled
+        assert_eq!(apu.read(0xFF26) & 0b10, 0b10);
+
+        run_steps(&mut apu, 2); // One length clock
+        assert_eq!(apu.read(0xFF26) & 0b10, 0b10);
+        run_steps(&mut apu, 2);
+        assert_eq!(apu.read(0xFF26) & 0b10, 0);
+    }
+
+    #[test]
+    fn test_dac_off_disables_channel() {
+        let mut apu = new_apu();
+        apu.write(0xFF12, 0xF0);
+        apu.write(0xFF14, 0b1000_0000);
+        assert_eq!(apu.read(0xFF26) & 0b01, 0b01);
+        apu.write(0xFF12, 0x00);
+        assert_eq!(apu.read(0xFF26) & 0b01, 0);
+    }
+
+    #[test]
+    fn test_sweep_overflow_on_trigger() {
+        let mut apu = new_apu();
+        apu.write(0xFF12, 0xF0);
+        apu.write(0xFF10, 0b0001_0001); // Period 1, add, shift 1
+        apu.write(0xFF13, 0xFF);
+        apu.write(0xFF14, 0b1000_0111); // Trigger at 2047, 2047 + 1023 overflows
+        assert_eq!(apu.read(0xFF26) & 0b01, 0);
+    }
+
+    #[test]
+    fn test_sweep_overflow_after_update() {
+        let mut apu = new_apu();
+        apu.write(0xFF12, 0xF0);
+        apu.write(0xFF10, 0b0001_0001); // Period 1, add, shift 1
+        apu.write(0xFF13, 0x00);
+        apu.write(0xFF14, 0b1000_0100); // Trigger at 1024, 1024 + 512 is fine
+        run_steps(&mut apu, 2);
+        assert_eq!(apu.read(0xFF26) & 0b01, 0b01);
+        run_steps(&mut apu, 1); // Sweep clocked on step 2: 1536, then 1536 + 768 overflows
+        assert_eq!(apu.read(0xFF26) & 0b01, 0);
+    }
+
+    #[test]
+    fn test_power_off_clears_registers() {
+        let mut apu = new_apu();
+        apu.write(0xFF11, 0xBF);
+        apu.write(0xFF12, 0xF3);
+        apu.write(0xFF26, 0x00);
+        assert_eq!(apu.read(0xFF11), 0x3F);
+        assert_eq!(apu.read(0xFF12), 0x00);
+        assert_eq!(apu.read(0xFF26), 0x70);
+
+        // Writes are ignored until powered back on
+        apu.write(0xFF12, 0xF3);
+        assert_eq!(apu.read(0xFF12), 0x00);
+        apu.write(0xFF26, 0x80);
+        apu.write(0xFF12, 0xF3);
+        assert_eq!(apu.read(0xFF12), 0xF3);
+    }
+
+    #[test]
+    fn test_wave_and_noise_read_masks() {
+        let mut apu = new_apu();
+        for addr in 0xFF1A..=0xFF23 {
+            apu.write(addr, 0x00);
+        }
+        let masks = [0x7F, 0xFF, 0x9F, 0xFF, 0xBF, 0xFF, 0xFF, 0x00, 0x00, 0xBF];
+        for (addr, mask) in (0xFF1A..=0xFF23).zip(masks) {
+            assert_eq!(apu.read(addr), mask, "{:04X}", addr);
+        }
+    }
+
+    #[test]
+    fn test_wave_ram_survives_power_off() {
+        let mut apu = new_apu();
+        for (i, addr) in (0xFF30..=0xFF3F).enumerate() {
+            apu.write(addr, i as u8 * 0x11);
+        }
+        apu.write(0xFF26, 0x00);
+        apu.write(0xFF3F, 0xAB); // Still writable while off
+        apu.write(0xFF26, 0x80);
+        assert_eq!(apu.read(0xFF30), 0x00);
+        assert_eq!(apu.read(0xFF37), 0x77);
+        assert_eq!(apu.read(0xFF3F), 0xAB);
+    }
+
+    #[test]
+    fn test_wave_channel_plays_wave_ram() {
+        let mut apu = new_apu();
+        apu.write(0xFF30, 0x9F);
+        apu.write(0xFF1A, 0x80); // DAC on
+        apu.write(0xFF1C, 0b0010_0000); // 100% volume
+        apu.write(0xFF1D, 0xF0);
+        apu.write(0xFF1E, 0b1000_0111); // Trigger, sample period of 32 T-cycles
+        assert_eq!(apu.read(0xFF26) & 0b100, 0b100);
+
+        // After the 6 T-cycle trigger delay, playback starts at the second sample
+        for _ in 0..10 {
+            apu.step(4);
+        }
+        assert_eq!(apu.ch3.output(), 0x0F);
+        for _ in 0..8 {
+            apu.step(4);
+        }
+        assert_eq!(apu.ch3.output(), 0x00);
+    }
+
+    #[test]
+    fn test_noise_lfsr() {
+        let mut noise = Noise::new();
+        noise.write(3, 0b0000_1000, true); // 7-bit mode, fastest clock
+        noise.write(2, 0xF0, true);
+        noise.write(4, 0b1000_0000, true);
+        // 7-bit LFSR repeats every 127 clocks
+        let pattern: Vec<u8> = (0..127)
+            .map(|_| {
+                noise.step(8);
+                noise.output()
+            })
+            .collect();
+        let repeat: Vec<u8> = (0..127)
+            .map(|_| {
+                noise.step(8);
+                noise.output()
+            })
+            .collect();
+        assert_eq!(pattern, repeat);
+        assert!(pattern.contains(&0) && pattern.contains(&15));
+    }
+
+    #[test]
+    fn test_sample_output_rate() {
+        let buffer = Rc::new(RefCell::new(AudioBuffer::new()));
+        buffer.borrow_mut().sample_rate = 44100;
+        let mut apu = Apu::new(Rc::clone(&buffer));
+        // One second of T-cycles
+        for _ in 0..4194304 / 4 {
+            apu.step(4);
+        }
+        let samples = buffer.borrow().samples.len();
+        assert!((samples as i32 - 44100 * 2).abs() <= 2, "{}", samples);
+    }
+
+    #[test]
+    fn test_mixer_panning() {
+        let buffer = Rc::new(RefCell::new(AudioBuffer::new()));
+        let mut apu = Apu::new(Rc::clone(&buffer));
+        apu.write(0xFF24, 0x77);
+        apu.write(0xFF25, 0b0000_0010); // Channel 2 on the right only
+        apu.write(0xFF17, 0xF0);
+        apu.write(0xFF16, 0b1000_0000); // 50% duty
+        apu.write(0xFF18, 0x00);
+        apu.write(0xFF19, 0b1000_0110); // Trigger, ~1 kHz
+        for _ in 0..4194304 / 10 / 4 {
+            apu.step(4);
+        }
+        let samples = std::mem::take(&mut buffer.borrow_mut().samples);
+        let peak = |side: usize| {
+            samples
+                .iter()
+                .skip(side)
+                .step_by(2)
+                .map(|s| s.unsigned_abs())
+                .max()
+                .unwrap()
+        };
+        assert_eq!(peak(0), 0);
+        assert!(peak(1) > 4000, "{}", peak(1));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,165 @@
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+const DIVISORS: [u32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+// Channel 4, pseudo-random noise from a linear feedback shift register
+pub struct Noise {
+    enabled: bool,
+    clock_shift: u8,
+    short_mode: bool, // NR43.3: 7-bit LFSR instead of 15-bit
+    divisor_code: u8,
+    timer: u32,
+    lfsr: u16,
+    pub(super) length: LengthCounter,
+    envelope: Envelope,
+}
+impl Noise {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            clock_shift: 0,
+            short_mode: false,
+            divisor_code: 0,
+            timer: 0,
+            lfsr: 0x7FFF,
+            length: LengthCounter::new(64),
+            envelope: Envelope::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Digital output, 0 to 15
+    pub fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        (!self.lfsr & 0b1) as u8 * self.envelope.volume()
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    fn period(&self) -> u32 {
+        DIVISORS[self.divisor_code as usize] << self.clock_shift
+    }
+
+    pub fn step(&mut self, t_cycles: u32) {
+        // Clock shifts 14 and 15 stop the LFSR
+        if self.clock_shift >= 14 {
+            return;
+        }
+        let mut remaining = t_cycles;
+        while remaining >= self.timer {
+            remaining -= self.timer;
+            self.timer = self.period();
+            self.clock_lfsr();
+        }
+        self.timer -= remaining;
+    }
+
+    fn clock_lfsr(&mut self) {
+        let feedback = (self.lfsr ^ (self.lfsr >> 1)) & 0b1;
+        self.lfsr = (self.lfsr >> 1) | (feedback << 14);
+        if self.short_mode {
+            self.lfsr = (self.lfsr & !(1 << 6)) | (feedback << 6);
+        }
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled();
+        self.timer = self.period();
+        self.lfsr = 0x7FFF;
+        self.envelope.trigger();
+    }
+
+    // `reg` is 0 to 4 for the unused 0xFF1F and NR41 to NR44
+    pub fn read(&self, reg: u16) -> u8 {
+        match reg {
+            0 => 0xFF,
+            1 => 0xFF, // Write only
+            2 => self.envelope.read(),
+            3 => (self.clock_shift << 4) | ((self.short_mode as u8) << 3) | self.divisor_code,
+            4 => 0xBF | ((self.length.enabled() as u8) << 6),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write(&mut self, reg: u16, value: u8, next_step_clocks_length: bool) {
+        match reg {
+            0 => {}
+            1 => self.length.load(value),
+            2 => {
+                self.envelope.write(value);
+                if !self.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => {
+                self.clock_shift = value >> 4;
+                self.short_mode = value & 0b0000_1000 != 0;
+                self.divisor_code = value & 0b0000_0111;
+            }
+            4 => {
+                let trigger = value & 0b1000_0000 != 0;
+                if self
+                    .length
+                    .write_control(value & 0b0100_0000 != 0, trigger, next_step_clocks_length)
+                {
+                    self.enabled = false;
+                }
+                if trigger {
+                    self.trigger();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn power_off(&mut self) {
+        let mut length = std::mem::replace(&mut self.length, LengthCounter::new(64));
+        length.power_off();
+        *self = Self::new();
+        self.length = length;
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.bool(self.enabled);
+        state.u8(self.clock_shift);
+        state.bool(self.short_mode);
+        state.u8(self.divisor_code);
+        state.u32(self.timer);
+        state.u16(self.lfsr);
+        self.length.save_state(state);
+        self.envelope.save_state(state);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.enabled = state.bool()?;
+        self.clock_shift = state.u8()?;
+        self.short_mode = state.bool()?;
+        self.divisor_code = state.u8()?;
+        self.timer = state.u32()?;
+        self.lfsr = state.u16()?;
+        if self.clock_shift > 15 || self.divisor_code > 7 || self.timer > 112 << 15 {
+            return Err(StateError::Corrupt);
+        }
+        self.length.load_state(state)?;
+        self.envelope.load_state(state)
+    }
+}
@@ -0,0 +1,252 @@
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// 12.5%, 25%, 50%, 75%, read from bit 7 down
+const DUTY_PATTERNS: [u8; 4] = [0b0000_0001, 0b1000_0001, 0b1000_0111, 0b0111_1110];
+
+// Channel 1 only: NR10
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    timer: u8,
+    shadow_frequency: u16,
+    enabled: bool,
+    negate_used: bool, // Leaving negate mode after a negated calculation disables the channel
+}
+
+// Square wave channel. Channel 1 has a frequency sweep, channel 2 doesn't.
+pub struct Pulse {
+    enabled: bool,
+    duty: u8,
+    duty_step: u8,
+    frequency: u16, // 11 bits, NRx3 and the low 3 bits of NRx4
+    timer: u32,
+    pub(super) length: LengthCounter,
+    envelope: Envelope,
+    sweep: Option<Sweep>,
+}
+impl Pulse {
+    pub fn new(has_sweep: bool) -> Self {
+        Self {
+            enabled: false,
+            duty: 0,
+            duty_step: 0,
+            frequency: 0,
+            timer: 0,
+            length: LengthCounter::new(64),
+            envelope: Envelope::new(),
+            sweep: has_sweep.then_some(Sweep {
+                period: 0,
+                negate: false,
+                shift: 0,
+                timer: 0,
+                shadow_frequency: 0,
+                enabled: false,
+                negate_used: false,
+            }),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Digital output, 0 to 15
+    pub fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        let bit = (DUTY_PATTERNS[self.duty as usize] >> (7 - self.duty_step)) & 0b1;
+        bit * self.envelope.volume()
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    pub fn step(&mut self, t_cycles: u32) {
+        let mut remaining = t_cycles;
+        while remaining >= self.timer {
+            remaining -= self.timer;
+            self.timer = (2048 - self.frequency as u32) * 4;
+            self.duty_step = (self.duty_step + 1) & 0b111;
+        }
+        self.timer -= remaining;
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_sweep(&mut self) {
+        let Some(sweep) = self.sweep.as_mut() else {
+            return;
+        };
+        if sweep.timer > 0 {
+            sweep.timer -= 1;
+        }
+        if sweep.timer > 0 {
+            return;
+        }
+        sweep.timer = if sweep.period == 0 { 8 } else { sweep.period };
+        if !sweep.enabled || sweep.period == 0 {
+            return;
+        }
+
+        let new_frequency = self.sweep_frequency();
+        let sweep = self.sweep.as_mut().unwrap();
+        if new_frequency <= 2047 && sweep.shift != 0 {
+            sweep.shadow_frequency = new_frequency;
+            self.frequency = new_frequency;
+            // Overflow check again with the new frequency, but don't write it back
+            self.sweep_frequency();
+        }
+    }
+
+    // Next frequency from the shadow register. Overflowing 2047 disables the channel.
+    fn sweep_frequency(&mut self) -> u16 {
+        let sweep = self.sweep.as_mut().unwrap();
+        let delta = sweep.shadow_frequency >> sweep.shift;
+        let new_frequency = if sweep.negate {
+            sweep.negate_used = true;
+            sweep.shadow_frequency - delta
+        } else {
+            sweep.shadow_frequency + delta
+        };
+        if new_frequency > 2047 {
+            self.enabled = false;
+        }
+        new_frequency
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled();
+        self.timer = (2048 - self.frequency as u32) * 4;
+        self.envelope.trigger();
+
+        if let Some(sweep) = self.sweep.as_mut() {
+            sweep.shadow_frequency = self.frequency;
+            sweep.timer = if sweep.period == 0 { 8 } else { sweep.period };
+            sweep.enabled = sweep.period != 0 || sweep.shift != 0;
+            sweep.negate_used = false;
+            if sweep.shift != 0 {
+                self.sweep_frequency();
+            }
+        }
+    }
+
+    // `reg` is 0 to 4 for NRx0 to NRx4
+    pub fn read(&self, reg: u16) -> u8 {
+        match reg {
+            0 => match &self.sweep {
+                Some(sweep) => 0x80 | (sweep.period << 4) | ((sweep.negate as u8) << 3) | sweep.shift,
+                None => 0xFF,
+            },
+            1 => (self.duty << 6) | 0x3F,
+            2 => self.envelope.read(),
+            3 => 0xFF, // Write only
+            4 => 0xBF | ((self.length.enabled() as u8) << 6),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write(&mut self, reg: u16, value: u8, next_step_clocks_length: bool) {
+        match reg {
+            0 => {
+                if let Some(sweep) = self.sweep.as_mut() {
+                    sweep.period = (value >> 4) & 0b111;
+                    sweep.negate = value & 0b0000_1000 != 0;
+                    sweep.shift = value & 0b0000_0111;
+                    if sweep.negate_used && !sweep.negate {
+                        self.enabled = false;
+                    }
+                }
+            }
+            1 => {
+                self.duty = value >> 6;
+                self.length.load(value);
+            }
+            2 => {
+                self.envelope.write(value);
+                if !self.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            4 => {
+                self.frequency = (self.frequency & 0xFF) | ((value as u16 & 0b111) << 8);
+                let trigger = value & 0b1000_0000 != 0;
+                if self
+                    .length
+                    .write_control(value & 0b0100_0000 != 0, trigger, next_step_clocks_length)
+                {
+                    self.enabled = false;
+                }
+                if trigger {
+                    self.trigger();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn power_off(&mut self) {
+        let mut length = std::mem::replace(&mut self.length, LengthCounter::new(64));
+        length.power_off();
+        *self = Self::new(self.sweep.is_some());
+        self.length = length;
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.bool(self.enabled);
+        state.u8(self.duty);
+        state.u8(self.duty_step);
+        state.u16(self.frequency);
+        state.u32(self.timer);
+        self.length.save_state(state);
+        self.envelope.save_state(state);
+        if let Some(sweep) = &self.sweep {
+            state.u8(sweep.period);
+            state.bool(sweep.negate);
+            state.u8(sweep.shift);
+            state.u8(sweep.timer);
+            state.u16(sweep.shadow_frequency);
+            state.bool(sweep.enabled);
+            state.bool(sweep.negate_used);
+        }
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.enabled = state.bool()?;
+        self.duty = state.u8()?;
+        self.duty_step = state.u8()?;
+        self.frequency = state.u16()?;
+        self.timer = state.u32()?;
+        if self.duty > 3 || self.duty_step > 7 || self.frequency > 2047 || self.timer > 2048 * 4 {
+            return Err(StateError::Corrupt);
+        }
+        self.length.load_state(state)?;
+        self.envelope.load_state(state)?;
+        if let Some(sweep) = &mut self.sweep {
+            sweep.period = state.u8()?;
+            sweep.negate = state.bool()?;
+            sweep.shift = state.u8()?;
+            sweep.timer = state.u8()?;
+            sweep.shadow_frequency = state.u16()?;
+            sweep.enabled = state.bool()?;
+            sweep.negate_used = state.bool()?;
+            if sweep.period > 7 || sweep.shift > 7 || sweep.timer > 8 || sweep.shadow_frequency > 2047 {
+                return Err(StateError::Corrupt);
+            }
+        }
+        Ok(())
+    }
+}
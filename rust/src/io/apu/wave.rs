@@ -0,0 +1,171 @@
+use super::length::LengthCounter;
+use crate::savestate::{StateError, StateReader, StateWriter};
+
+// Channel 3, plays 32 4-bit samples from wave RAM
+pub struct Wave {
+    enabled: bool,
+    dac_enabled: bool, // NR30.7
+    volume_code: u8,   // 0: mute, 1: 100%, 2: 50%, 3: 25%
+    frequency: u16,
+    timer: u32,
+    position: u8, // Sample index into wave RAM (0 to 31)
+    sample_buffer: u8,
+    pub(super) length: LengthCounter,
+    wave_ram: [u8; 16],
+}
+impl Wave {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            dac_enabled: false,
+            volume_code: 0,
+            frequency: 0,
+            timer: 0,
+            position: 0,
+            sample_buffer: 0,
+            length: LengthCounter::new(256),
+            wave_ram: [0; 16],
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Digital output, 0 to 15
+    pub fn output(&self) -> u8 {
+        if !self.enabled || self.volume_code == 0 {
+            return 0;
+        }
+        self.sample_buffer >> (self.volume_code - 1)
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        self.dac_enabled
+    }
+
+    pub fn step(&mut self, t_cycles: u32) {
+        let mut remaining = t_cycles;
+        while remaining >= self.timer {
+            remaining -= self.timer;
+            self.timer = (2048 - self.frequency as u32) * 2;
+            self.position = (self.position + 1) & 0x1F;
+            if self.enabled {
+                let byte = self.wave_ram[self.position as usize / 2];
+                // High nibble is played first
+                self.sample_buffer = if self.position.is_multiple_of(2) {
+                    byte >> 4
+                } else {
+                    byte & 0x0F
+                };
+            }
+        }
+        self.timer -= remaining;
+    }
+
+    pub fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        // The first sample is read after an extra 6 T-cycle delay, the sample buffer isn't refreshed
+        self.timer = (2048 - self.frequency as u32) * 2 + 6;
+        self.position = 0;
+    }
+
+    // `reg` is 0 to 4 for NR30 to NR34
+    pub fn read(&self, reg: u16) -> u8 {
+        match reg {
+            0 => 0x7F | ((self.dac_enabled as u8) << 7),
+            1 => 0xFF, // Write only
+            2 => 0x9F | (self.volume_code << 5),
+            3 => 0xFF, // Write only
+            4 => 0xBF | ((self.length.enabled() as u8) << 6),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write(&mut self, reg: u16, value: u8, next_step_clocks_length: bool) {
+        match reg {
+            0 => {
+                self.dac_enabled = value & 0b1000_0000 != 0;
+                if !self.dac_enabled {
+                    self.enabled = false;
+                }
+            }
+            1 => self.length.load(value),
+            2 => self.volume_code = (value >> 5) & 0b11,
+            3 => self.frequency = (self.frequency & 0x700) | value as u16,
+            4 => {
+                self.frequency = (self.frequency & 0xFF) | ((value as u16 & 0b111) << 8);
+                let trigger = value & 0b1000_0000 != 0;
+                if self
+                    .length
+                    .write_control(value & 0b0100_0000 != 0, trigger, next_step_clocks_length)
+                {
+                    self.enabled = false;
+                }
+                if trigger {
+                    self.trigger();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // 0xFF30 to 0xFF3F. While the channel plays, the CPU can only reach the byte being played.
+    pub fn read_wave_ram(&self, addr: u16) -> u8 {
+        if self.enabled {
+            return self.wave_ram[self.position as usize / 2];
+        }
+        self.wave_ram[(addr & 0x0F) as usize]
+    }
+
+    pub fn write_wave_ram(&mut self, addr: u16, value: u8) {
+        if self.enabled {
+            self.wave_ram[self.position as usize / 2] = value;
+            return;
+        }
+        self.wave_ram[(addr & 0x0F) as usize] = value;
+    }
+
+    // Wave RAM and the length counter survive power off
+    pub fn power_off(&mut self) {
+        let mut length = std::mem::replace(&mut self.length, LengthCounter::new(256));
+        length.power_off();
+        let wave_ram = self.wave_ram;
+        *self = Self::new();
+        self.length = length;
+        self.wave_ram = wave_ram;
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.bool(self.enabled);
+        state.bool(self.dac_enabled);
+        state.u8(self.volume_code);
+        state.u16(self.frequency);
+        state.u32(self.timer);
+        state.u8(self.position);
+        state.u8(self.sample_buffer);
+        self.length.save_state(state);
+        state.bytes(&self.wave_ram);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.enabled = state.bool()?;
+        self.dac_enabled = state.bool()?;
+        self.volume_code = state.u8()?;
+        self.frequency = state.u16()?;
+        self.timer = state.u32()?;
+        self.position = state.u8()?;
+        if self.volume_code > 3 || self.frequency > 2047 || self.timer > 2048 * 2 + 6 || self.position > 31 {
+            return Err(StateError::Corrupt);
+        }
+        self.sample_buffer = state.u8()?;
+        self.length.load_state(state)?;
+        state.bytes(&mut self.wave_ram)
+    }
+}
@@ -1,8 +1,15 @@
+use crate::savestate::{StateError, StateReader, StateWriter};
+
 pub struct Joypad {
     pressed: u8,
     inverse_select_action: bool,
     inverse_select_dpad: bool,
 }
+impl Default for Joypad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl Joypad {
     pub fn new() -> Self {
         Self {
@@ -14,6 +21,17 @@ impl Joypad {
     pub fn set_buttons(&mut self, pressed: u8) {
         self.pressed = pressed;
     }
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.pressed);
+        state.bool(self.inverse_select_action);
+        state.bool(self.inverse_select_dpad);
+    }
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.pressed = state.u8()?;
+        self.inverse_select_action = state.bool()?;
+        self.inverse_select_dpad = state.bool()?;
+        Ok(())
+    }
     pub fn read(&self) -> u8 {
         let mut lo = 0b0000_1111;
         if !self.inverse_select_action {
@@ -1,54 +1,206 @@
+use crate::savestate::{StateError, StateReader, StateWriter};
+
 #[repr(u8)]
-enum ColorIndices {
+#[derive(Copy, Clone, PartialEq)]
+pub enum ColorIndices {
     Zero,
     One,
     Two,
     Three,
 }
-struct Pixel {
-    color: ColorIndices,
-    uses_obp1: bool,
+impl From<u8> for ColorIndices {
+    fn from(value: u8) -> Self {
+        match value & 0b11 {
+            0 => ColorIndices::Zero,
+            1 => ColorIndices::One,
+            2 => ColorIndices::Two,
+            3 => ColorIndices::Three,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Pixel {
+    pub color: ColorIndices,
+    pub uses_obp1: bool,
+    pub bg_priority: bool, // OBJ only: BG colors 1-3 are drawn over this pixel
 }
 impl Pixel {
-    fn new(color: ColorIndices, uses_obp1: bool) -> Self {
-        Self { color, uses_obp1 }
+    fn new(color: ColorIndices, uses_obp1: bool, bg_priority: bool) -> Self {
+        Self {
+            color,
+            uses_obp1,
+            bg_priority,
+        }
     }
 }
 
+// An object selected by OAM scan for the current scanline
+#[derive(Copy, Clone)]
+pub struct Sprite {
+    pub y: u8, // Screen Y + 16
+    pub x: u8, // Screen X + 8
+    pub tile: u8,
+    pub flags: u8,
+    pub fetched: bool,
+}
+impl Sprite {
+    pub fn new(attributes: &[u8]) -> Self {
+        Self {
+            y: attributes[0],
+            x: attributes[1],
+            tile: attributes[2],
+            flags: attributes[3],
+            fetched: false,
+        }
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.bytes(&[self.y, self.x, self.tile, self.flags]);
+        state.bool(self.fetched);
+    }
+
+    pub fn load_state(state: &mut StateReader) -> Result<Self, StateError> {
+        let mut attributes = [0; 4];
+        state.bytes(&mut attributes)?;
+        let mut sprite = Self::new(&attributes);
+        sprite.fetched = state.bool()?;
+        Ok(sprite)
+    }
+}
+
+// Ring buffer, pixels are shifted out from the front.
 pub struct PixelFifo {
     queue: [Pixel; 16],
+    head: usize,
+    len: usize,
 }
 impl PixelFifo {
     pub fn new() -> Self {
         Self {
-            queue: core::array::from_fn(|_| Pixel::new(ColorIndices::Zero, true)),
+            queue: core::array::from_fn(|_| Pixel::new(ColorIndices::Zero, false, false)),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    pub fn push(&mut self, pixel: Pixel) {
+        debug_assert!(self.len < self.queue.len());
+        self.queue[(self.head + self.len) % self.queue.len()] = pixel;
+        self.len += 1;
+    }
+
+    // Mix an OBJ pixel in at `index` pixels from the front. Pixels already there win unless transparent.
+    pub fn merge(&mut self, index: usize, pixel: Pixel) {
+        while self.len <= index {
+            self.push(Pixel::new(ColorIndices::Zero, false, false));
+        }
+        let slot = &mut self.queue[(self.head + index) % self.queue.len()];
+        if slot.color == ColorIndices::Zero {
+            *slot = pixel;
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<Pixel> {
+        if self.len == 0 {
+            return None;
+        }
+        let pixel = self.queue[self.head];
+        self.head = (self.head + 1) % self.queue.len();
+        self.len -= 1;
+        Some(pixel)
+    }
+
+    // Only the queued pixels, front first
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.len as u8);
+        for i in 0..self.len {
+            let pixel = self.queue[(self.head + i) % self.queue.len()];
+            state.u8(pixel.color as u8 | (pixel.uses_obp1 as u8) << 2 | (pixel.bg_priority as u8) << 3);
+        }
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.clear();
+        let len = state.u8()? as usize;
+        if len > self.queue.len() {
+            return Err(StateError::Corrupt);
+        }
+        for _ in 0..len {
+            let byte = state.u8()?;
+            self.push(Pixel::new(byte.into(), byte & 0b0100 != 0, byte & 0b1000 != 0));
         }
+        Ok(())
     }
 }
 
-#[derive(PartialEq)]
+#[derive(Copy, Clone, PartialEq)]
 enum FetcherModes {
     GetTileID,
     GetTileLow,
     GetTileHigh,
-    Sleep,
     Push,
 }
-struct Fetcher {
-    fifo: PixelFifo,
+
+// Background fetcher. Every step except Push takes 2 dots.
+// Push is retried every dot until the FIFO is empty, which keeps one tile fetch per 8 pixels shifted out.
+// The first tile of a scanline is fetched twice, the first fetch is thrown away (6 dots).
+pub struct Fetcher {
+    pub fifo: PixelFifo,
+    pub obj_fifo: PixelFifo,
     mode: FetcherModes,
     tick: u32,
+    map_addr: u16, // VRAM offset of the first tile in the tile map row being read
+    tile_x: u8,    // Which tile of the tile map row is being read (0 to 31)
+    tile_row: u8,  // Which row of the 8x8 tile is being read
+    tile_id: u8,
+    tile_lo: u8,
+    tile_hi: u8,
+    dummy_fetch: bool,
 }
 impl Fetcher {
     pub fn new() -> Self {
         Self {
             fifo: PixelFifo::new(),
+            obj_fifo: PixelFifo::new(),
             mode: FetcherModes::GetTileID,
             tick: 0,
+            map_addr: 0,
+            tile_x: 0,
+            tile_row: 0,
+            tile_id: 0,
+            tile_lo: 0,
+            tile_hi: 0,
+            dummy_fetch: false,
         }
     }
-    pub fn start(map_addr: u32, tile_row: u32) {}
-    fn step(&mut self) {
+
+    pub fn start_scanline(&mut self, map_addr: u16, tile_x: u8, tile_row: u8) {
+        self.obj_fifo.clear();
+        self.dummy_fetch = true;
+        self.start(map_addr, tile_x, tile_row);
+    }
+
+    pub fn start(&mut self, map_addr: u16, tile_x: u8, tile_row: u8) {
+        self.fifo.clear();
+        self.mode = FetcherModes::GetTileID;
+        self.tick = 0;
+        self.map_addr = map_addr;
+        self.tile_x = tile_x;
+        self.tile_row = tile_row;
+    }
+
+    pub fn step(&mut self, vram: &[u8], lcdc: u8) {
         self.tick += 1;
         if self.tick < 2 && self.mode != FetcherModes::Push {
             return;
@@ -57,20 +209,115 @@ impl Fetcher {
 
         match self.mode {
             FetcherModes::GetTileID => {
+                self.tile_id = vram[(self.map_addr + (self.tile_x & 0x1F) as u16) as usize];
                 self.mode = FetcherModes::GetTileLow;
             }
             FetcherModes::GetTileLow => {
+                self.tile_lo = vram[self.tile_data_addr(lcdc)];
                 self.mode = FetcherModes::GetTileHigh;
             }
             FetcherModes::GetTileHigh => {
-                self.mode = FetcherModes::Sleep;
-            }
-            FetcherModes::Sleep => {
-                self.mode = FetcherModes::Push;
+                self.tile_hi = vram[self.tile_data_addr(lcdc) + 1];
+                if self.dummy_fetch {
+                    self.dummy_fetch = false;
+                    self.mode = FetcherModes::GetTileID;
+                } else {
+                    self.mode = FetcherModes::Push;
+                }
             }
             FetcherModes::Push => {
+                if !self.fifo.is_empty() {
+                    return; // Wait until FIFO is drained
+                }
+                for bit in (0..8).rev() {
+                    let lo = (self.tile_lo >> bit) & 0b1;
+                    let hi = (self.tile_hi >> bit) & 0b1;
+                    self.fifo.push(Pixel::new(((hi << 1) | lo).into(), false, false));
+                }
+                self.tile_x = self.tile_x.wrapping_add(1);
                 self.mode = FetcherModes::GetTileID;
             }
         }
     }
+
+    // Fetch one row of an object and mix it into the OBJ FIFO. `lx` is the screen X of the FIFO front.
+    pub fn fetch_sprite(&mut self, vram: &[u8], lcdc: u8, ly: u8, lx: u32, sprite: &Sprite) {
+        // LCDC.2: 8x16 objects ignore bit 0 of the tile index
+        let (height, tile) = if lcdc & 0b0000_0100 != 0 {
+            (16, sprite.tile & 0xFE)
+        } else {
+            (8, sprite.tile)
+        };
+        let mut row = ly.wrapping_add(16).wrapping_sub(sprite.y) % height;
+        if sprite.flags & 0b0100_0000 != 0 {
+            row = height - 1 - row; // Y flip
+        }
+        let addr = tile as usize * 16 + row as usize * 2;
+        let (lo, hi) = (vram[addr], vram[addr + 1]);
+
+        for i in 0..8 {
+            // Pixels left of the FIFO front are clipped
+            let screen_x = sprite.x as i32 - 8 + i;
+            if screen_x < lx as i32 {
+                continue;
+            }
+            let bit = if sprite.flags & 0b0010_0000 != 0 { i } else { 7 - i }; // X flip
+            let color = (((hi >> bit) & 0b1) << 1) | ((lo >> bit) & 0b1);
+            let pixel = Pixel::new(
+                color.into(),
+                sprite.flags & 0b0001_0000 != 0,
+                sprite.flags & 0b1000_0000 != 0,
+            );
+            self.obj_fifo.merge((screen_x - lx as i32) as usize, pixel);
+        }
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        self.fifo.save_state(state);
+        self.obj_fifo.save_state(state);
+        state.u8(self.mode as u8);
+        state.u32(self.tick);
+        state.u16(self.map_addr);
+        state.u8(self.tile_x);
+        state.u8(self.tile_row);
+        state.u8(self.tile_id);
+        state.u8(self.tile_lo);
+        state.u8(self.tile_hi);
+        state.bool(self.dummy_fetch);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.fifo.load_state(state)?;
+        self.obj_fifo.load_state(state)?;
+        self.mode = match state.u8()? {
+            0 => FetcherModes::GetTileID,
+            1 => FetcherModes::GetTileLow,
+            2 => FetcherModes::GetTileHigh,
+            3 => FetcherModes::Push,
+            _ => return Err(StateError::Corrupt),
+        };
+        self.tick = state.u32()?;
+        self.map_addr = state.u16()?;
+        self.tile_x = state.u8()?;
+        self.tile_row = state.u8()?;
+        // The last tile map row starts at 0x9FE0
+        if self.tick > 1 || self.map_addr > 0x1FE0 || self.tile_row > 7 {
+            return Err(StateError::Corrupt);
+        }
+        self.tile_id = state.u8()?;
+        self.tile_lo = state.u8()?;
+        self.tile_hi = state.u8()?;
+        self.dummy_fetch = state.bool()?;
+        Ok(())
+    }
+
+    fn tile_data_addr(&self, lcdc: u8) -> usize {
+        // LCDC.4: 0x8000 method (unsigned ID) or 0x8800 method (signed ID, based at 0x9000)
+        let tile_addr = if lcdc & 0b0001_0000 != 0 {
+            self.tile_id as usize * 16
+        } else {
+            (0x1000 + self.tile_id as i8 as i32 * 16) as usize
+        };
+        tile_addr + self.tile_row as usize * 2
+    }
 }
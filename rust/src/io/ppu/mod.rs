@@ -1,6 +1,41 @@
 mod fetcher;
 use crate::SharedFrameBuffer;
+use crate::io::ppu::fetcher::{ColorIndices, Fetcher, Pixel, Sprite};
+use crate::savestate::{StateError, StateReader, StateWriter};
 
+// DMG shades as RGBA: white, light gray, dark gray, black
+const SHADES: [[u8; 4]; 4] = [
+    [0xFF, 0xFF, 0xFF, 0xFF],
+    [0xAA, 0xAA, 0xAA, 0xFF],
+    [0x55, 0x55, 0x55, 0xFF],
+    [0x00, 0x00, 0x00, 0xFF],
+];
+
+// Save states keep the frame buffers as shade indices, 4 pixels to a byte instead of 16 bytes of RGBA
+const PACKED_FRAME_LEN: usize = 160 * 144 / 4;
+
+fn pack_shades(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks(16)
+        .map(|pixels| {
+            // Red is 0xFF, 0xAA, 0x55 or 0x00, for shades 0 to 3
+            pixels
+                .chunks(4)
+                .enumerate()
+                .fold(0, |byte, (i, pixel)| byte | (3 - pixel[0] / 0x55) << (i * 2))
+        })
+        .collect()
+}
+
+fn unpack_shades(packed: &[u8], rgba: &mut [u8]) {
+    for (byte, pixels) in packed.iter().zip(rgba.chunks_mut(16)) {
+        for (i, pixel) in pixels.chunks_mut(4).enumerate() {
+            pixel.copy_from_slice(&SHADES[(byte >> (i * 2)) as usize & 0b11]);
+        }
+    }
+}
+
+// Discriminants are the STAT mode bits
+#[derive(Copy, Clone, PartialEq)]
 enum Modes {
     HBlank,
     VBlank,
@@ -27,9 +62,23 @@ pub struct Ppu {
     mode: Modes,
     dots: u32,
     lx: u32,
-    tile_row: u32,
-    tile_map_row_addr: u32,
+    discard: u8, // Pixels left to drop for SCX fine scroll
+    fetcher: Fetcher,
+    sprites: Vec<Sprite>,                // Up to 10 objects on the current scanline, in OAM order
+    obj_stall: u32,                      // Dots left before the pixel pipeline resumes after an OBJ fetch
+    penalized_tile: Option<(bool, i32)>, // (window, tile) that already cost the OBJ fetch extra dots
+
+    wy_triggered: bool,  // WY matched LY at some point this frame
+    window_active: bool, // Fetcher switched to the window on this scanline
+    window_line: u8,     // Internal line counter, only increments on scanlines the window was drawn on
+
+    stat_line: bool, // OR of all enabled STAT sources, the interrupt fires on its rising edge
+
+    first_line: bool, // First line after the LCD is turned on: no OAM scan, STAT reads mode 0
+    off_dots: u32,    // Dots since the last frame while the LCD is off
+    frame_done: bool,
 
+    back_buffer: Vec<u8>,
     pub front_buffer: SharedFrameBuffer,
 }
 
@@ -54,74 +103,362 @@ impl Ppu {
             mode: Modes::OAMScan,
             dots: 0,
             lx: 0,
-            tile_row: 0,          // Which row of the 8x8 tile is being read
-            tile_map_row_addr: 0, // Address of the first tile in the row of 8x8 tiles on the map being read
+            discard: 0,
+            fetcher: Fetcher::new(),
+            sprites: Vec::with_capacity(10),
+            obj_stall: 0,
+            penalized_tile: None,
+
+            wy_triggered: false,
+            window_active: false,
+            window_line: 0,
+
+            stat_line: false,
+
+            first_line: false,
+            off_dots: 0,
+            frame_done: false,
 
+            back_buffer: vec![0xFF; 160 * 144 * 4],
             front_buffer: graphics_buffer,
         }
     }
 
+    // LCD off and palettes cleared, the boot ROM turns it on
+    pub fn power_on(graphics_buffer: SharedFrameBuffer) -> Self {
+        let mut ppu = Self::new(graphics_buffer);
+        ppu.write_register(0xFF40, 0x00);
+        ppu.r_stat = 0x00;
+        ppu.r_bgp = 0x00;
+        ppu
+    }
+
     /// Returns (vblank_interrupt, stat_interrupt)
     pub fn step(&mut self, t_cycles: u32) -> (bool, bool) {
         let mut vblank_triggered = false;
         let mut lcd_stat_triggered = false;
 
+        if !self.lcd_enabled() {
+            // Keep signalling frames so the frontend doesn't wait on a VBlank that never comes
+            self.off_dots += t_cycles;
+            if self.off_dots >= 70224 {
+                self.off_dots -= 70224;
+                self.frame_done = true;
+            }
+            return (false, false);
+        }
+
         for _ in 0..t_cycles {
-            self.dots += 1;
-
-            match self.mode {
-                Modes::OAMScan => {
-                    if self.dots == 80 {
-                        self.lx = 0;
-                        self.tile_row = self.r_ly as u32 % 8;
-                        self.mode = Modes::Drawing;
-                    }
+            vblank_triggered |= self.tick_dot();
+            lcd_stat_triggered |= self.update_stat();
+        }
+
+        (vblank_triggered, lcd_stat_triggered)
+    }
+
+    /// Returns true on entering VBlank
+    fn tick_dot(&mut self) -> bool {
+        self.dots += 1;
+
+        match self.mode {
+            Modes::OAMScan => {
+                // One OAM entry is checked every 2 dots
+                if self.dots.is_multiple_of(2) && !self.first_line {
+                    self.scan_oam_entry((self.dots / 2 - 1) as usize);
                 }
-                Modes::Drawing => {
-                    // TODO: fetch pixel data into FIFO
-                    // TODO: put a pixel from FIFO onto display
-                    // TODO: Enter HBlank if scanline has all 160 pixels
+                if self.dots == 80 {
+                    self.first_line = false;
+                    self.start_drawing();
+                    self.mode = Modes::Drawing;
                 }
-                Modes::HBlank => {
-                    if self.dots >= 456 {
-                        self.dots -= 456;
-                        self.r_ly += 1;
-                        if self.r_ly >= 144 {
-                            self.mode = Modes::VBlank;
-                        } else {
-                            self.mode = Modes::OAMScan;
-                        }
+            }
+            Modes::Drawing => self.draw_dot(),
+            Modes::HBlank => {
+                if self.dots >= 456 {
+                    self.dots -= 456;
+                    self.r_ly += 1;
+                    if self.r_ly >= 144 {
+                        self.mode = Modes::VBlank;
+                        self.front_buffer.borrow_mut().copy_from_slice(&self.back_buffer);
+                        self.frame_done = true;
+                        return true;
+                    } else {
+                        self.mode = Modes::OAMScan;
                     }
                 }
-                Modes::VBlank => {
-                    if self.dots >= 456 {
-                        self.dots -= 456;
+            }
+            Modes::VBlank => {
+                // Line 153 quirk: LY reads 0 after the first M-cycle of the line
+                if self.r_ly == 153 && self.dots == 4 {
+                    self.r_ly = 0;
+                }
+                if self.dots >= 456 {
+                    self.dots -= 456;
+                    if self.r_ly == 0 {
+                        self.wy_triggered = false;
+                        self.window_line = 0;
+                        self.mode = Modes::OAMScan;
+                    } else {
                         self.r_ly += 1;
-                        if self.r_ly >= 154 {
-                            self.r_ly -= 154;
-                            self.mode = Modes::OAMScan;
-                        }
                     }
                 }
             }
         }
+        false
+    }
 
-        (vblank_triggered, lcd_stat_triggered)
+    fn draw_dot(&mut self) {
+        // Mode 3 is 172 dots, plus SCX % 8 discarded pixels, 6 per window restart and 6 to 11 per object
+        if self.obj_stall > 0 {
+            self.obj_stall -= 1;
+            return;
+        }
+        if !self.window_active && self.window_reached() {
+            self.start_window();
+        }
+        let penalty = self.fetch_sprites();
+        if penalty > 0 {
+            self.obj_stall = penalty - 1;
+            return;
+        }
+        self.fetcher.step(&self.vram, self.r_lcdc);
+        if let Some(pixel) = self.fetcher.fifo.pop() {
+            if self.discard > 0 {
+                self.discard -= 1;
+                return;
+            }
+            let obj_pixel = self.fetcher.obj_fifo.pop();
+            self.draw_pixel(pixel, obj_pixel);
+            self.lx += 1;
+            if self.lx == 160 {
+                if self.window_active {
+                    self.window_line += 1;
+                }
+                self.mode = Modes::HBlank;
+            }
+        }
+    }
+
+    /// Syncs the STAT mode and LYC bits. Returns true on a rising edge of the STAT interrupt line.
+    fn update_stat(&mut self) -> bool {
+        let lyc_match = self.r_ly == self.r_lyc;
+        let mode = if self.first_line { Modes::HBlank } else { self.mode };
+        self.r_stat = (self.r_stat & 0xF8) | ((lyc_match as u8) << 2) | mode as u8;
+
+        let mode_source = match mode {
+            Modes::HBlank => self.r_stat & 0b0000_1000 != 0,
+            // The mode 2 source also fires when entering VBlank
+            Modes::VBlank => {
+                self.r_stat & 0b0001_0000 != 0 || (self.r_stat & 0b0010_0000 != 0 && self.r_ly == 144 && self.dots == 0)
+            }
+            Modes::OAMScan => self.r_stat & 0b0010_0000 != 0,
+            Modes::Drawing => false,
+        };
+        let lyc_source = self.r_stat & 0b0100_0000 != 0 && lyc_match;
+
+        let line = mode_source || lyc_source;
+        let rising_edge = line && !self.stat_line;
+        self.stat_line = line;
+        rising_edge
+    }
+
+    fn scan_oam_entry(&mut self, index: usize) {
+        if index == 0 {
+            self.sprites.clear();
+        }
+        if self.sprites.len() == 10 {
+            return;
+        }
+        let sprite = Sprite::new(&self.oam[index * 4..index * 4 + 4]);
+        // LCDC.2: OBJ size 8x8 or 8x16
+        let height = if self.r_lcdc & 0b0000_0100 != 0 { 16 } else { 8 };
+        let line = self.r_ly as u32 + 16;
+        if line >= sprite.y as u32 && line < sprite.y as u32 + height {
+            self.sprites.push(sprite);
+        }
+    }
+
+    /// Returns the number of dots the fetch stalls the pixel pipeline for
+    fn fetch_sprites(&mut self) -> u32 {
+        // LCDC.1: OBJ enable
+        if self.r_lcdc & 0b0000_0010 == 0 {
+            return 0;
+        }
+        let mut penalty = 0;
+        // Lower X first, then lower OAM index. Pixels mixed in first keep priority.
+        while let Some(sprite) = self
+            .sprites
+            .iter_mut()
+            .filter(|s| !s.fetched && s.x as u32 <= self.lx + 8)
+            .min_by_key(|s| s.x)
+        {
+            sprite.fetched = true;
+            let sprite = *sprite;
+            penalty += self.sprite_penalty(&sprite);
+            self.fetcher
+                .fetch_sprite(&self.vram, self.r_lcdc, self.r_ly, self.lx, &sprite);
+        }
+        penalty
+    }
+
+    fn sprite_penalty(&mut self, sprite: &Sprite) -> u32 {
+        if sprite.x == 0 {
+            return 11;
+        }
+        // Wait for the BG fetcher to finish the tile under the leftmost OBJ pixel. Only the first OBJ on a tile pays.
+        let origin = if self.window_active {
+            self.r_wx as i32 - 7
+        } else {
+            -((self.r_scx % 8) as i32)
+        };
+        let offset = sprite.x as i32 - 8 - origin;
+        let tile = (self.window_active, offset.div_euclid(8));
+        if self.penalized_tile == Some(tile) {
+            return 6;
+        }
+        self.penalized_tile = Some(tile);
+        6 + 5u32.saturating_sub(offset.rem_euclid(8) as u32)
+    }
+
+    fn start_drawing(&mut self) {
+        self.lx = 0;
+        self.window_active = false;
+        self.penalized_tile = None;
+        if self.r_ly == self.r_wy {
+            self.wy_triggered = true;
+        }
+
+        // LCDC.3: BG tile map at 0x9800 or 0x9C00
+        let bg_map_addr = if self.r_lcdc & 0b0000_1000 != 0 { 0x1C00 } else { 0x1800 };
+        let y = self.r_ly.wrapping_add(self.r_scy);
+        self.fetcher
+            .start_scanline(bg_map_addr + (y as u16 / 8) * 32, self.r_scx / 8, y % 8);
+        self.discard = self.r_scx % 8;
+    }
+
+    fn window_reached(&self) -> bool {
+        // LCDC.5: Window enable. The window starts at screen X = WX - 7.
+        self.r_lcdc & 0b0010_0000 != 0 && self.wy_triggered && self.lx + 7 >= self.r_wx as u32
+    }
+
+    fn start_window(&mut self) {
+        self.window_active = true;
+
+        // LCDC.6: Window tile map at 0x9800 or 0x9C00
+        let window_map_addr = if self.r_lcdc & 0b0100_0000 != 0 { 0x1C00 } else { 0x1800 };
+        let y = self.window_line;
+        // Restart the fetcher, discarding whatever background pixels are left in the FIFO
+        self.fetcher.start(window_map_addr + (y as u16 / 8) * 32, 0, y % 8);
+        // WX < 7 pushes the window partially off the left edge
+        self.discard = 7u8.saturating_sub(self.r_wx);
+    }
+
+    fn draw_pixel(&mut self, bg_pixel: Pixel, obj_pixel: Option<Pixel>) {
+        // LCDC.0: BG and Window are blank (white) when cleared
+        let bg_color = if self.r_lcdc & 0b0000_0001 != 0 {
+            bg_pixel.color
+        } else {
+            ColorIndices::Zero
+        };
+        let shade = match obj_pixel {
+            // Color 0 is transparent for objects
+            Some(obj) if obj.color != ColorIndices::Zero && !(obj.bg_priority && bg_color != ColorIndices::Zero) => {
+                let palette = if obj.uses_obp1 { self.r_obp1 } else { self.r_obp0 };
+                (palette >> (obj.color as u8 * 2)) & 0b11
+            }
+            _ if self.r_lcdc & 0b0000_0001 != 0 => (self.r_bgp >> (bg_color as u8 * 2)) & 0b11,
+            _ => 0,
+        };
+        let offset = (self.r_ly as usize * 160 + self.lx as usize) * 4;
+        self.back_buffer[offset..offset + 4].copy_from_slice(&SHADES[shade as usize]);
+    }
+
+    /// True once per frame, on VBlank or every 70224 dots while the LCD is off
+    pub fn take_frame_done(&mut self) -> bool {
+        std::mem::take(&mut self.frame_done)
+    }
+
+    fn lcd_enabled(&self) -> bool {
+        // LCDC.7: LCD & PPU enable
+        self.r_lcdc & 0b1000_0000 != 0
+    }
+
+    fn disable_lcd(&mut self) {
+        self.r_ly = 0;
+        self.dots = 0;
+        self.off_dots = 0;
+        self.mode = Modes::HBlank;
+        self.r_stat &= 0xF8;
+        self.stat_line = false;
+
+        self.back_buffer.fill(0xFF);
+        self.front_buffer.borrow_mut().fill(0xFF);
+    }
+
+    fn enable_lcd(&mut self) {
+        // Line 0 skips OAM scan and runs 4 dots short
+        self.dots = 4;
+        self.first_line = true;
+        self.mode = Modes::OAMScan;
+        self.wy_triggered = false;
+        self.window_line = 0;
+        self.sprites.clear();
+    }
+
+    // VRAM is locked while the PPU is drawing
+    fn vram_blocked(&self) -> bool {
+        self.mode == Modes::Drawing
+    }
+
+    // OAM is locked during OAM scan and drawing. Line 0 after enabling the LCD does no OAM scan.
+    fn oam_blocked(&self) -> bool {
+        match self.mode {
+            Modes::OAMScan => !self.first_line,
+            Modes::Drawing => true,
+            _ => false,
+        }
     }
 
     pub fn read_vram(&self, addr: u16) -> u8 {
+        if self.vram_blocked() {
+            return 0xFF;
+        }
+        self.unblocked_read_vram(addr)
+    }
+
+    // Used by OAM DMA, which reads VRAM regardless of the PPU mode
+    pub fn unblocked_read_vram(&self, addr: u16) -> u8 {
         self.vram[(addr & 0x1FFF) as usize]
     }
 
     pub fn write_vram(&mut self, addr: u16, value: u8) {
+        if self.vram_blocked() {
+            return;
+        }
         self.vram[(addr & 0x1FFF) as usize] = value;
     }
 
     pub fn read_oam(&self, addr: u16) -> u8 {
+        if self.oam_blocked() {
+            return 0xFF;
+        }
+        self.unblocked_read_oam(addr)
+    }
+
+    // Used by debug reads, which see OAM regardless of the PPU mode
+    pub fn unblocked_read_oam(&self, addr: u16) -> u8 {
         self.oam[(addr & 0xFF) as usize]
     }
 
     pub fn write_oam(&mut self, addr: u16, value: u8) {
+        if self.oam_blocked() {
+            return;
+        }
+        self.unblocked_write_oam(addr, value);
+    }
+
+    // Used by OAM DMA, which writes OAM regardless of the PPU mode
+    pub fn unblocked_write_oam(&mut self, addr: u16, value: u8) {
         self.oam[(addr & 0xFF) as usize] = value;
     }
 
@@ -142,9 +479,138 @@ impl Ppu {
         }
     }
 
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.bytes(&self.vram);
+        state.bytes(&self.oam);
+        state.bytes(&[
+            self.r_lcdc,
+            self.r_stat,
+            self.r_scy,
+            self.r_scx,
+            self.r_ly,
+            self.r_lyc,
+            self.r_bgp,
+            self.r_obp0,
+            self.r_obp1,
+            self.r_wy,
+            self.r_wx,
+        ]);
+
+        state.u8(self.mode as u8);
+        state.u32(self.dots);
+        state.u32(self.lx);
+        state.u8(self.discard);
+        self.fetcher.save_state(state);
+        state.u8(self.sprites.len() as u8);
+        for sprite in &self.sprites {
+            sprite.save_state(state);
+        }
+        state.u32(self.obj_stall);
+        state.bool(self.penalized_tile.is_some());
+        if let Some((window, tile)) = self.penalized_tile {
+            state.bool(window);
+            state.i32(tile);
+        }
+
+        state.bool(self.wy_triggered);
+        state.bool(self.window_active);
+        state.u8(self.window_line);
+        state.bool(self.stat_line);
+        state.bool(self.first_line);
+        state.u32(self.off_dots);
+        state.bool(self.frame_done);
+
+        state.bytes(&pack_shades(&self.back_buffer));
+        state.bytes(&pack_shades(&self.front_buffer.borrow()));
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        state.bytes(&mut self.vram)?;
+        state.bytes(&mut self.oam)?;
+        let mut registers = [0; 11];
+        state.bytes(&mut registers)?;
+        [
+            self.r_lcdc,
+            self.r_stat,
+            self.r_scy,
+            self.r_scx,
+            self.r_ly,
+            self.r_lyc,
+            self.r_bgp,
+            self.r_obp0,
+            self.r_obp1,
+            self.r_wy,
+            self.r_wx,
+        ] = registers;
+
+        self.mode = match state.u8()? {
+            0 => Modes::HBlank,
+            1 => Modes::VBlank,
+            2 => Modes::OAMScan,
+            3 => Modes::Drawing,
+            _ => return Err(StateError::Corrupt),
+        };
+        self.dots = state.u32()?;
+        self.lx = state.u32()?;
+        self.discard = state.u8()?;
+        // LY and the dot counters index OAM and the frame buffer, so they have to fit the mode
+        let line_valid = match self.mode {
+            Modes::OAMScan => self.r_ly < 144 && self.dots < 80,
+            Modes::Drawing => self.r_ly < 144 && self.dots < 456 && self.lx < 160,
+            Modes::HBlank => self.r_ly < 144 && self.dots < 456,
+            Modes::VBlank => (self.r_ly == 0 || (144..=153).contains(&self.r_ly)) && self.dots < 456,
+        };
+        if !line_valid || self.lx > 160 || self.discard > 7 {
+            return Err(StateError::Corrupt);
+        }
+        self.fetcher.load_state(state)?;
+        let sprites = state.u8()?;
+        if sprites > 10 {
+            return Err(StateError::Corrupt);
+        }
+        self.sprites.clear();
+        for _ in 0..sprites {
+            self.sprites.push(Sprite::load_state(state)?);
+        }
+        self.obj_stall = state.u32()?;
+        if self.obj_stall > 10 {
+            return Err(StateError::Corrupt);
+        }
+        self.penalized_tile = match state.bool()? {
+            true => Some((state.bool()?, state.i32()?)),
+            false => None,
+        };
+
+        self.wy_triggered = state.bool()?;
+        self.window_active = state.bool()?;
+        self.window_line = state.u8()?;
+        self.stat_line = state.bool()?;
+        self.first_line = state.bool()?;
+        self.off_dots = state.u32()?;
+        if self.window_line > 144 || self.off_dots >= 70224 {
+            return Err(StateError::Corrupt);
+        }
+        self.frame_done = state.bool()?;
+
+        let mut packed = [0; PACKED_FRAME_LEN];
+        state.bytes(&mut packed)?;
+        unpack_shades(&packed, &mut self.back_buffer);
+        state.bytes(&mut packed)?;
+        unpack_shades(&packed, &mut self.front_buffer.borrow_mut());
+        Ok(())
+    }
+
     pub fn write_register(&mut self, addr: u16, value: u8) {
         match addr {
-            0xFF40 => self.r_lcdc = value,
+            0xFF40 => {
+                let was_enabled = self.lcd_enabled();
+                self.r_lcdc = value;
+                match (was_enabled, self.lcd_enabled()) {
+                    (true, false) => self.disable_lcd(),
+                    (false, true) => self.enable_lcd(),
+                    _ => {}
+                }
+            }
             0xFF41 => self.r_stat = (value & 0xF8) | (self.r_stat & 0x07),
             0xFF42 => self.r_scy = value,
             0xFF43 => self.r_scx = value,
@@ -159,3 +625,532 @@ impl Ppu {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn ppu_with(lcdc: u8, scx: u8, sprite_xs: &[u8]) -> Ppu {
+        let mut ppu = Ppu::new(Rc::new(RefCell::new(vec![0; 160 * 144 * 4])));
+        ppu.write_register(0xFF40, lcdc);
+        ppu.write_register(0xFF43, scx);
+        for (i, &x) in sprite_xs.iter().enumerate() {
+            let addr = 0xFE00 + i as u16 * 4;
+            ppu.unblocked_write_oam(addr, 16); // Y: covers line 0
+            ppu.unblocked_write_oam(addr + 1, x);
+        }
+        ppu
+    }
+
+    // Dots spent in mode 3 on the first scanline
+    fn mode3_length(ppu: &mut Ppu) -> u32 {
+        while !matches!(ppu.mode, Modes::Drawing) {
+            ppu.step(1);
+        }
+        let mut dots = 0;
+        while matches!(ppu.mode, Modes::Drawing) {
+            ppu.step(1);
+            dots += 1;
+        }
+        dots
+    }
+
+    // Run to the next VBlank and return the frame as shade indices, 0 (white) to 3 (black)
+    fn render_frame(ppu: &mut Ppu) -> Vec<u8> {
+        while !ppu.take_frame_done() {
+            ppu.step(1);
+        }
+        let frame = ppu.front_buffer.borrow();
+        frame
+            .chunks(4)
+            .map(|rgba| SHADES.iter().position(|shade| shade == rgba).unwrap() as u8)
+            .collect()
+    }
+
+    fn line(frame: &[u8], ly: usize) -> &[u8] {
+        &frame[ly * 160..ly * 160 + 160]
+    }
+
+    // Tile 1: every row is colors 0 0 2 2 1 1 3 3. Tile 2: a diagonal of color 3, row N has it at X = N.
+    fn write_test_tiles(ppu: &mut Ppu, base: usize) {
+        for row in 0..8 {
+            ppu.vram[base + 16 + row * 2] = 0x0F;
+            ppu.vram[base + 16 + row * 2 + 1] = 0x33;
+            ppu.vram[base + 32 + row * 2] = 0x80 >> row;
+            ppu.vram[base + 32 + row * 2 + 1] = 0x80 >> row;
+        }
+    }
+
+    #[test]
+    fn test_background_tiles() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.write_register(0xFF47, 0xE4); // BGP: color N is shade N
+        write_test_tiles(&mut ppu, 0x0000);
+        ppu.vram[0x1800] = 1;
+        ppu.vram[0x1801] = 2;
+        ppu.vram[0x1800 + 32 + 31] = 1; // Second row, last column
+
+        let frame = render_frame(&mut ppu);
+        for ly in 0..8 {
+            let mut expected = vec![0, 0, 2, 2, 1, 1, 3, 3, 0, 0, 0, 0, 0, 0, 0, 0];
+            expected[8 + ly] = 3;
+            assert_eq!(&line(&frame, ly)[..16], expected, "line {}", ly);
+            assert!(line(&frame, ly)[16..].iter().all(|&shade| shade == 0));
+        }
+        // Only 20 of the 32 columns are on screen
+        assert!(frame[8 * 160..].iter().all(|&shade| shade == 0));
+
+        // Palette is applied per color
+        ppu.write_register(0xFF47, 0b00_01_10_11);
+        assert_eq!(&line(&render_frame(&mut ppu), 0)[..8], [3, 3, 1, 1, 2, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_background_signed_tile_data() {
+        // LCDC.4 cleared: IDs 0-127 are read from 0x9000, 128-255 from 0x8800
+        let mut ppu = ppu_with(0x81, 0, &[]);
+        ppu.write_register(0xFF47, 0xE4);
+        write_test_tiles(&mut ppu, 0x1000);
+        write_test_tiles(&mut ppu, 0x0800 - 16); // As 0x80 and 0x81
+        ppu.vram[0x1800] = 1;
+        ppu.vram[0x1801] = 2;
+        ppu.vram[0x1802] = 0x80;
+        ppu.vram[0x1803] = 0x81;
+
+        let frame = render_frame(&mut ppu);
+        let tiles = [0, 0, 2, 2, 1, 1, 3, 3, 3, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(&line(&frame, 0)[..32], [tiles, tiles].concat());
+        // Unsigned tiles at 0x8000 are unused
+        write_test_tiles(&mut ppu, 0x0000);
+        assert_eq!(render_frame(&mut ppu), frame);
+    }
+
+    #[test]
+    fn test_background_scroll() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.write_register(0xFF47, 0xE4);
+        write_test_tiles(&mut ppu, 0x0000);
+        ppu.vram[0x1800 + 32] = 1; // Row 1, column 0
+        ppu.vram[0x1800 + 32 + 1] = 2;
+
+        // SCY picks the tile map row and the row within the tile
+        ppu.write_register(0xFF42, 11);
+        let frame = render_frame(&mut ppu);
+        assert_eq!(&line(&frame, 0)[..16], [0, 0, 2, 2, 1, 1, 3, 3, 0, 0, 0, 3, 0, 0, 0, 0]);
+        assert_eq!(&line(&frame, 4)[..16], [0, 0, 2, 2, 1, 1, 3, 3, 0, 0, 0, 0, 0, 0, 0, 3]);
+        assert!(line(&frame, 5).iter().all(|&shade| shade == 0));
+
+        // The first SCX % 8 pixels of the first tile are dropped
+        for scx in 1..8 {
+            ppu.write_register(0xFF43, scx);
+            let frame = render_frame(&mut ppu);
+            let expected = [0, 0, 2, 2, 1, 1, 3, 3, 0, 0, 0, 3, 0, 0, 0, 0];
+            let scx = scx as usize;
+            assert_eq!(&line(&frame, 0)[..16 - scx], &expected[scx..], "SCX {}", scx);
+        }
+        // And whole tiles with SCX / 8, the diagonal on row 3 lands on X = 0
+        ppu.write_register(0xFF43, 8 + 3);
+        let frame = render_frame(&mut ppu);
+        assert_eq!(line(&frame, 0)[0], 3);
+        assert!(line(&frame, 0)[1..].iter().all(|&shade| shade == 0));
+    }
+
+    #[test]
+    fn test_background_scroll_wraps() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.write_register(0xFF47, 0xE4);
+        write_test_tiles(&mut ppu, 0x0000);
+        ppu.vram[0x1800 + 31] = 1; // Row 0, column 31
+        ppu.vram[0x1800] = 2;
+
+        // The map wraps around both ways, SCY = 248 shows row 31 at the top then row 0 from line 8
+        ppu.write_register(0xFF42, 248);
+        ppu.write_register(0xFF43, 253);
+        let frame = render_frame(&mut ppu);
+        assert!(line(&frame, 0).iter().all(|&shade| shade == 0));
+        assert_eq!(&line(&frame, 8)[..11], [1, 3, 3, 3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&line(&frame, 10)[..11], [1, 3, 3, 0, 0, 3, 0, 0, 0, 0, 0]);
+    }
+
+    fn run_to_line(ppu: &mut Ppu, ly: u8) {
+        while ppu.r_ly != ly {
+            ppu.step(1);
+        }
+    }
+
+    #[test]
+    fn test_window_line_counter() {
+        // Window on with its map at 0x9C00, row 0 of the map is diagonals and row 1 is tile 1. BG stays white.
+        let mut ppu = ppu_with(0xF1, 0, &[]);
+        ppu.write_register(0xFF47, 0xE4);
+        write_test_tiles(&mut ppu, 0x0000);
+        ppu.vram[0x1C00..0x1C20].fill(2);
+        ppu.vram[0x1C20..0x1C40].fill(1);
+        ppu.write_register(0xFF4A, 0); // WY
+        ppu.write_register(0xFF4B, 7); // WX
+
+        // Window disabled for lines 4 to 9, then pushed off screen for lines 12 and 13
+        run_to_line(&mut ppu, 4);
+        ppu.write_register(0xFF40, 0xD1);
+        run_to_line(&mut ppu, 10);
+        ppu.write_register(0xFF40, 0xF1);
+        run_to_line(&mut ppu, 12);
+        ppu.write_register(0xFF4B, 167);
+        run_to_line(&mut ppu, 14);
+        // WX < 7 hides the first 7 - WX window pixels
+        ppu.write_register(0xFF4B, 3);
+        let frame = render_frame(&mut ppu);
+
+        let diagonal = |x: usize| {
+            let mut pixels = [0; 16];
+            pixels[x] = 3;
+            pixels[x + 8] = 3;
+            pixels
+        };
+        for ly in 0..4 {
+            assert_eq!(line(&frame, ly)[..16], diagonal(ly), "line {}", ly);
+        }
+        for ly in (4..10).chain(12..14) {
+            assert!(line(&frame, ly).iter().all(|&shade| shade == 0), "line {}", ly);
+        }
+        // The counter only moves on lines the window was drawn on, so these are window lines 4 to 7
+        assert_eq!(line(&frame, 10)[..16], diagonal(4));
+        assert_eq!(line(&frame, 11)[..16], diagonal(5));
+        assert_eq!(line(&frame, 14)[..16], diagonal(6 - 4));
+        assert_eq!(line(&frame, 15)[..16], diagonal(7 - 4));
+        // Window line 8 starts map row 1
+        assert_eq!(line(&frame, 16)[..8], [1, 1, 3, 3, 0, 0, 2, 2]);
+
+        // WY is checked again on the next frame and the counter restarts
+        ppu.write_register(0xFF4A, 2);
+        ppu.write_register(0xFF4B, 7);
+        let frame = render_frame(&mut ppu);
+        assert!(line(&frame, 1).iter().all(|&shade| shade == 0));
+        assert_eq!(line(&frame, 2)[..16], diagonal(0));
+    }
+
+    fn write_object(ppu: &mut Ppu, index: u16, y: u8, x: u8, tile: u8, flags: u8) {
+        for (i, value) in [y, x, tile, flags].into_iter().enumerate() {
+            ppu.unblocked_write_oam(0xFE00 + index * 4 + i as u16, value);
+        }
+    }
+
+    // Tiles 1 and 2 from write_test_tiles, 3 is solid color 3, 4 is color 1 on the left half, 5 is solid color 2
+    fn ppu_with_objects(lcdc: u8) -> Ppu {
+        let mut ppu = ppu_with(lcdc, 0, &[]);
+        ppu.write_register(0xFF47, 0xE4);
+        ppu.write_register(0xFF48, 0xE4);
+        write_test_tiles(&mut ppu, 0x0000);
+        ppu.vram[0x30..0x40].fill(0xFF);
+        for row in 0..8 {
+            ppu.vram[0x40 + row * 2] = 0xF0;
+            ppu.vram[0x50 + row * 2 + 1] = 0xFF;
+        }
+        ppu
+    }
+
+    #[test]
+    fn test_object_line_limit() {
+        let mut ppu = ppu_with_objects(0x93);
+        // Not on line 0, doesn't take a slot
+        write_object(&mut ppu, 0, 16 + 8, 8, 3, 0);
+        // The first 10 in OAM order are picked, even though the last two are further left
+        for i in 1..11 {
+            write_object(&mut ppu, i, 16, 8 + 24 + i as u8 * 10, 3, 0);
+        }
+        write_object(&mut ppu, 11, 16, 8, 3, 0);
+        write_object(&mut ppu, 12, 16, 8 + 10, 3, 0);
+
+        let frame = render_frame(&mut ppu);
+        assert!(line(&frame, 0)[..24].iter().all(|&shade| shade == 0));
+        for i in 1..11 {
+            let x = 24 + i * 10;
+            assert!(
+                line(&frame, 0)[x..x + 8].iter().all(|&shade| shade == 3),
+                "object {}",
+                i
+            );
+        }
+        // Line 8 only has object 0
+        assert!(line(&frame, 8)[..8].iter().all(|&shade| shade == 3));
+        assert!(line(&frame, 8)[8..].iter().all(|&shade| shade == 0));
+
+        // LCDC.1 hides them all
+        ppu.write_register(0xFF40, 0x91);
+        assert!(render_frame(&mut ppu).iter().all(|&shade| shade == 0));
+    }
+
+    #[test]
+    fn test_object_priority() {
+        let mut ppu = ppu_with_objects(0x93);
+        // Lines 0-7: lower X wins, whatever the OAM order
+        write_object(&mut ppu, 0, 16, 8 + 4, 5, 0);
+        write_object(&mut ppu, 1, 16, 8 + 2, 3, 0);
+        // Lines 8-15: same X, lower OAM index wins
+        write_object(&mut ppu, 2, 16 + 8, 8 + 2, 5, 0);
+        write_object(&mut ppu, 3, 16 + 8, 8 + 2, 3, 0);
+        // Lines 16-23: color 0 is transparent and lets the next object through
+        write_object(&mut ppu, 4, 16 + 16, 8 + 2, 4, 0);
+        write_object(&mut ppu, 5, 16 + 16, 8 + 2, 3, 0);
+
+        let frame = render_frame(&mut ppu);
+        assert_eq!(line(&frame, 0)[..14], [0, 0, 3, 3, 3, 3, 3, 3, 3, 3, 2, 2, 0, 0]);
+        assert_eq!(line(&frame, 8)[..12], [0, 0, 2, 2, 2, 2, 2, 2, 2, 2, 0, 0]);
+        assert_eq!(line(&frame, 16)[..12], [0, 0, 1, 1, 1, 1, 3, 3, 3, 3, 0, 0]);
+    }
+
+    #[test]
+    fn test_object_flip_and_palette() {
+        let mut ppu = ppu_with_objects(0x93);
+        ppu.write_register(0xFF49, 0x1B); // OBP1 reversed
+        write_object(&mut ppu, 0, 16, 8, 1, 0);
+        write_object(&mut ppu, 1, 16, 8 + 8, 1, 0b0010_0000); // X flip
+        write_object(&mut ppu, 2, 16, 8 + 16, 1, 0b0001_0000); // OBP1
+        write_object(&mut ppu, 3, 16 + 8, 8, 2, 0);
+        write_object(&mut ppu, 4, 16 + 8, 8 + 8, 2, 0b0100_0000); // Y flip
+        write_object(&mut ppu, 5, 16 + 8, 8 + 16, 2, 0b0110_0000); // Both
+
+        let frame = render_frame(&mut ppu);
+        // Color 0 stays transparent whatever the palette maps it to
+        assert_eq!(
+            line(&frame, 0)[..24],
+            [
+                [0, 0, 2, 2, 1, 1, 3, 3],
+                [3, 3, 1, 1, 2, 2, 0, 0],
+                [0, 0, 1, 1, 2, 2, 0, 0]
+            ]
+            .concat()
+        );
+        for row in 0..8 {
+            let ly = 8 + row;
+            assert_eq!(line(&frame, ly)[row], 3);
+            assert_eq!(line(&frame, ly)[8 + 7 - row], 3);
+            assert_eq!(line(&frame, ly)[16 + row], 3);
+            assert_eq!(
+                line(&frame, ly)[..24].iter().filter(|&&shade| shade == 3).count(),
+                3,
+                "line {}",
+                ly
+            );
+        }
+    }
+
+    #[test]
+    fn test_tall_objects() {
+        // LCDC.2: 8x16, bit 0 of the tile index is ignored so tile 3 draws tiles 2 then 3
+        let mut ppu = ppu_with_objects(0x97);
+        write_object(&mut ppu, 0, 16, 8, 3, 0);
+        write_object(&mut ppu, 1, 16, 8 + 8, 2, 0b0100_0000); // Y flip swaps the halves too
+
+        let frame = render_frame(&mut ppu);
+        for row in 0..8 {
+            let mut expected = [0; 16];
+            expected[row] = 3;
+            expected[8..].fill(3);
+            assert_eq!(line(&frame, row)[..16], expected, "line {}", row);
+
+            let mut expected = [3; 16];
+            expected[8..].fill(0);
+            expected[8 + 7 - row] = 3;
+            assert_eq!(line(&frame, 8 + row)[..16], expected, "line {}", 8 + row);
+        }
+        assert!(line(&frame, 16).iter().all(|&shade| shade == 0));
+    }
+
+    #[test]
+    fn test_object_behind_background() {
+        // BG colors 1-3 all show as shade 2 or 3, the object as shade 1
+        let mut ppu = ppu_with_objects(0x93);
+        ppu.write_register(0xFF47, 0b11_10_10_00);
+        ppu.write_register(0xFF48, 0b01_00_00_00);
+        ppu.vram[0x1800] = 1;
+        ppu.vram[0x1820] = 1;
+        write_object(&mut ppu, 0, 16, 8, 3, 0b1000_0000);
+        write_object(&mut ppu, 1, 16 + 8, 8, 3, 0);
+
+        // Only BG color 0 lets a background priority object through
+        let frame = render_frame(&mut ppu);
+        assert_eq!(line(&frame, 0)[..10], [1, 1, 2, 2, 2, 2, 3, 3, 0, 0]);
+        assert_eq!(line(&frame, 8)[..10], [1, 1, 1, 1, 1, 1, 1, 1, 0, 0]);
+
+        // With LCDC.0 cleared the background is white and never covers objects
+        ppu.write_register(0xFF40, 0x92);
+        let frame = render_frame(&mut ppu);
+        assert_eq!(line(&frame, 0)[..10], [1, 1, 1, 1, 1, 1, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_mode3_base_length() {
+        assert_eq!(mode3_length(&mut ppu_with(0x91, 0, &[])), 172);
+    }
+
+    #[test]
+    fn test_mode3_scx_discard() {
+        for scx in 0..16 {
+            assert_eq!(mode3_length(&mut ppu_with(0x91, scx, &[])), 172 + (scx % 8) as u32);
+        }
+    }
+
+    #[test]
+    fn test_mode3_window_restart() {
+        let mut ppu = ppu_with(0xB1, 0, &[]);
+        ppu.write_register(0xFF4A, 0); // WY
+        ppu.write_register(0xFF4B, 87); // WX
+        assert_eq!(mode3_length(&mut ppu), 172 + 6);
+    }
+
+    #[test]
+    fn test_mode3_sprite_penalty() {
+        // 6 dots per object, plus up to 5 while the BG fetcher finishes the tile under it
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 0, &[8])), 172 + 11);
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 0, &[10])), 172 + 9);
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 0, &[13])), 172 + 6);
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 0, &[15])), 172 + 6);
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 3, &[8])), 172 + 3 + 8);
+        // X = 0 always costs 11
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 5, &[0])), 172 + 5 + 11);
+        // Only the first object on a tile waits for the fetcher
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 0, &[8, 10])), 172 + 11 + 6);
+        assert_eq!(mode3_length(&mut ppu_with(0x93, 0, &[8, 16])), 172 + 11 + 11);
+        // OBJ disabled, no fetches
+        assert_eq!(mode3_length(&mut ppu_with(0x91, 0, &[8])), 172);
+    }
+
+    #[test]
+    fn test_scanline_length() {
+        let mut ppu = ppu_with(0x93, 5, &[8, 20, 40]);
+        let mut dots = 0;
+        while ppu.r_ly == 0 {
+            ppu.step(1);
+            dots += 1;
+        }
+        assert_eq!(dots, 456);
+    }
+
+    #[test]
+    fn test_lyc_interrupt() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.write_register(0xFF45, 10); // LYC
+        ppu.write_register(0xFF41, 0b0100_0000); // LYC source only
+        let mut fired_on = vec![];
+        for _ in 0..154 * 456 {
+            if ppu.step(1).1 {
+                fired_on.push(ppu.r_ly);
+            }
+            assert_eq!(ppu.read_register(0xFF41) & 0b100 != 0, ppu.r_ly == 10);
+        }
+        assert_eq!(fired_on, vec![10]);
+    }
+
+    #[test]
+    fn test_stat_mode_sources_share_one_line() {
+        // HBlank and OAM scan are back to back, so the line never drops between them
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.write_register(0xFF41, 0b0010_1000);
+        let mut fired = 0;
+        for _ in 0..456 * 3 {
+            if ppu.step(1).1 {
+                fired += 1;
+            }
+        }
+        // Line 0 OAM scan, then one per HBlank. OAM scan of lines 1 and 2 are blocked.
+        assert_eq!(fired, 4);
+    }
+
+    #[test]
+    fn test_line_153_quirk() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.write_register(0xFF45, 0);
+        while ppu.r_ly != 153 {
+            ppu.step(1);
+        }
+        ppu.step(4);
+        assert_eq!(ppu.read_register(0xFF44), 0);
+        assert_eq!(ppu.read_register(0xFF41) & 0b111, 0b101); // Still VBlank, LYC matches
+        ppu.step(452);
+        assert_eq!(ppu.read_register(0xFF41) & 0b11, 0b10);
+    }
+
+    #[test]
+    fn test_lcd_off_and_on() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.step(456 * 10 + 100);
+        ppu.write_register(0xFF40, 0x11);
+        assert_eq!(ppu.read_register(0xFF44), 0);
+        assert_eq!(ppu.read_register(0xFF41) & 0b11, 0);
+
+        // No VBlank while off, but a frame is still signalled
+        assert_eq!(ppu.step(70224), (false, false));
+        assert!(ppu.take_frame_done());
+        assert_eq!(ppu.read_register(0xFF44), 0);
+
+        // First line after enabling reads mode 0 instead of mode 2 and is 4 dots short
+        ppu.write_register(0xFF40, 0x91);
+        ppu.step(1);
+        assert_eq!(ppu.read_register(0xFF41) & 0b11, 0);
+        let mut dots = 1;
+        while ppu.r_ly == 0 {
+            ppu.step(1);
+            dots += 1;
+        }
+        assert_eq!(dots, 452);
+    }
+
+    #[test]
+    fn test_vram_oam_blocking() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        ppu.unblocked_write_oam(0xFE00, 0x42);
+
+        // Mode 2: OAM locked
+        ppu.write_vram(0x8000, 0x24);
+        ppu.write_oam(0xFE00, 0x00);
+        assert_eq!(ppu.read_vram(0x8000), 0x24);
+        assert_eq!(ppu.read_oam(0xFE00), 0xFF);
+
+        // Mode 3: VRAM and OAM locked
+        while ppu.mode != Modes::Drawing {
+            ppu.step(1);
+        }
+        ppu.write_vram(0x8000, 0x00);
+        assert_eq!(ppu.read_vram(0x8000), 0xFF);
+        assert_eq!(ppu.read_oam(0xFE00), 0xFF);
+        assert_eq!(ppu.unblocked_read_vram(0x8000), 0x24);
+        assert_eq!(ppu.unblocked_read_oam(0xFE00), 0x42);
+
+        // Mode 0: both unlocked
+        while ppu.mode != Modes::HBlank {
+            ppu.step(1);
+        }
+        assert_eq!(ppu.read_vram(0x8000), 0x24);
+        assert_eq!(ppu.read_oam(0xFE00), 0x42);
+    }
+
+    #[test]
+    fn test_load_state_rejects_out_of_range_counters() {
+        let mut ppu = ppu_with(0x91, 0, &[]);
+        while !(ppu.r_ly == 10 && matches!(ppu.mode, Modes::Drawing) && ppu.lx > 0) {
+            ppu.step(1);
+        }
+        let mut state = StateWriter::new(0, 0);
+        ppu.save_state(&mut state);
+        let data = state.finish();
+
+        // Past the 9 byte header, VRAM, OAM and LCDC to WX
+        let ly = 9 + 0x2000 + 0xA0 + 4;
+        let lx = 9 + 0x2000 + 0xA0 + 11 + 1 + 4;
+        for (offset, value) in [(ly, 154), (ly, 144), (lx, 160), (lx, 200)] {
+            let mut patched = data.clone();
+            patched[offset] = value;
+            let mut reader = StateReader::new(&patched, 0, 0).unwrap();
+            let result = ppu.load_state(&mut reader);
+            assert_eq!(result, Err(StateError::Corrupt), "{offset} = {value}");
+        }
+
+        let mut reader = StateReader::new(&data, 0, 0).unwrap();
+        ppu.load_state(&mut reader).unwrap();
+        reader.finish().unwrap();
+        render_frame(&mut ppu);
+    }
+}
@@ -1,10 +1,17 @@
+use crate::savestate::{StateError, StateReader, StateWriter};
+
 pub struct Serial {
     pub sb: u8,       // 0xFF01
     pub sc: u8,       // 0xFF02
     cycle_count: u32, // Cycle count (0 to 511)
     shift_count: u8,  // Bit count (0 to 8)
     current_char: u8,
-    pub log_buffer: String,
+    pub log_buffer: Option<String>, // Bytes sent, None until someone asks for them
+}
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl Serial {
     pub fn new() -> Self {
@@ -14,7 +21,7 @@ impl Serial {
             cycle_count: 0,
             shift_count: 0,
             current_char: 0,
-            log_buffer: String::new(),
+            log_buffer: None,
         }
     }
 
@@ -42,6 +49,27 @@ impl Serial {
         }
     }
 
+    // The log is test output, not hardware
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.sb);
+        state.u8(self.sc);
+        state.u32(self.cycle_count);
+        state.u8(self.shift_count);
+        state.u8(self.current_char);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.sb = state.u8()?;
+        self.sc = state.u8()?;
+        self.cycle_count = state.u32()?;
+        self.shift_count = state.u8()?;
+        self.current_char = state.u8()?;
+        if self.cycle_count > 512 || self.shift_count > 7 {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+
     pub fn step(&mut self, t_cycles: u32) -> bool {
         if self.sc & 0b1000_0001 != 0b1000_0001 {
             return false; // Transfer inactive, skip the rest
@@ -63,8 +91,9 @@ impl Serial {
                 self.sc &= 0b0111_1111; // Clear flag
 
                 // Log Output (for testing)
-                let c = self.current_char as char;
-                print!("{}", c);
+                if let Some(log) = &mut self.log_buffer {
+                    log.push(self.current_char as char);
+                }
                 request_interrupt = true;
             }
         } else {
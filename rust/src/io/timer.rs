@@ -1,3 +1,5 @@
+use crate::savestate::{StateError, StateReader, StateWriter};
+
 pub struct Timer {
     timer_counter: u8,
     timer_modulo: u8,
@@ -5,6 +7,13 @@ pub struct Timer {
     internal_counter: u16,
     interrupt_request: bool,
     cycles_until_tima_reload: u32,
+    frame_sequencer_clock: bool,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Timer {
@@ -18,6 +27,7 @@ impl Timer {
             internal_counter: 0,
             interrupt_request: false,
             cycles_until_tima_reload: 0,
+            frame_sequencer_clock: false,
         }
     }
 
@@ -40,8 +50,12 @@ impl Timer {
         }
 
         let signal_before = self.get_timer_signal();
+        let apu_bit_before = self.get_apu_signal();
         self.internal_counter = self.internal_counter.wrapping_add(t_cycles as u16);
         let signal_after = self.get_timer_signal();
+        if apu_bit_before && !self.get_apu_signal() {
+            self.frame_sequencer_clock = true;
+        }
 
         // If 1 -> 0
         if signal_before && !signal_after {
@@ -68,6 +82,40 @@ impl Timer {
         tac_target_bit && tac_enable_bit
     }
 
+    // The APU frame sequencer steps when internal_counter bit 12 (DIV bit 4) goes from 1 -> 0, at 512 Hz
+    fn get_apu_signal(&self) -> bool {
+        self.internal_counter & (0b1 << 12) != 0
+    }
+
+    pub fn save_state(&self, state: &mut StateWriter) {
+        state.u8(self.timer_counter);
+        state.u8(self.timer_modulo);
+        state.u8(self.timer_control);
+        state.u16(self.internal_counter);
+        state.bool(self.interrupt_request);
+        state.u32(self.cycles_until_tima_reload);
+        state.bool(self.frame_sequencer_clock);
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.timer_counter = state.u8()?;
+        self.timer_modulo = state.u8()?;
+        self.timer_control = state.u8()?;
+        self.internal_counter = state.u16()?;
+        self.interrupt_request = state.bool()?;
+        self.cycles_until_tima_reload = state.u32()?;
+        self.frame_sequencer_clock = state.bool()?;
+        if self.cycles_until_tima_reload > 4 {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+
+    // True once for every DIV falling edge seen by the APU since the last call
+    pub fn take_frame_sequencer_clock(&mut self) -> bool {
+        std::mem::take(&mut self.frame_sequencer_clock)
+    }
+
     fn inc_timer_counter(&mut self) {
         let (new_val, overflow) = self.timer_counter.overflowing_add(1);
         if overflow {
@@ -91,6 +139,10 @@ impl Timer {
         match addr {
             0xFF04 => {
                 let signal_before = self.get_timer_signal();
+                // Resetting DIV while the frame sequencer bit is set clocks it early
+                if self.get_apu_signal() {
+                    self.frame_sequencer_clock = true;
+                }
                 self.internal_counter = 0;
                 let signal_after = self.get_timer_signal();
                 if signal_before && !signal_after {
@@ -115,3 +167,32 @@ impl Timer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_sequencer_clock() {
+        let mut timer = Timer::new();
+        let mut clocks = 0;
+        // 4 frame sequencer periods of 8192 T-cycles
+        for _ in 0..4 * 8192 / 4 {
+            timer.step(4);
+            clocks += timer.take_frame_sequencer_clock() as u32;
+        }
+        assert_eq!(clocks, 4);
+
+        // DIV write while internal_counter bit 12 (DIV bit 4) is set clocks it once more
+        while timer.read(0xFF04) & 0b1_0000 == 0 {
+            timer.step(4);
+        }
+        timer.take_frame_sequencer_clock();
+        timer.write(0xFF04, 0);
+        assert!(timer.take_frame_sequencer_clock());
+
+        // Bit 12 is clear after the reset, no extra clock
+        timer.write(0xFF04, 0);
+        assert!(!timer.take_frame_sequencer_clock());
+    }
+}
@@ -1,5 +1,5 @@
 mod frb_generated; /* AUTO INJECTED BY flutter_rust_bridge. This line may not be accurate, and you can change it according to your needs. */
-use std::cell::{Cell, RefCell};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -10,65 +10,116 @@ pub mod cpu;
 pub mod io;
 pub mod mmu;
 pub mod api;
+pub mod savestate;
+pub mod rewind;
 
 // Is frame done? Check without locking Bus
 pub type FrameSignal = Rc<Cell<bool>>;
 pub type SharedFrameBuffer = Rc<RefCell<Vec<u8>>>;
-
-pub struct EmulatorState {
-    pub cpu: cpu::Cpu,
-    pub bus: mmu::Bus,
-}
+pub type CycleCounter = Rc<Cell<u64>>;
+pub type RumbleSignal = Rc<Cell<bool>>;
+pub type SharedAudioBuffer = Rc<RefCell<io::apu::AudioBuffer>>;
 
 // entrypoint
 pub struct DancyHandle {
-    // Hardware state shared pointer
-    state: Rc<RefCell<EmulatorState>>,
+    // Borrowed by the execution loop for a whole instruction
+    cpu: Rc<RefCell<cpu::Cpu>>,
+    // Borrowed by the execution loop for one M-cycle at a time
+    bus: Rc<RefCell<mmu::Bus>>,
     // Persistent asynx execution loop
     execution_future: Pin<Box<dyn Future<Output = ()>>>,
     // Shared signal
     frame_ready: FrameSignal,
     // Video buffer
     video_buffer: SharedFrameBuffer,
+    // Audio buffer
+    audio_buffer: SharedAudioBuffer,
+    // Bus cycle counter
+    cycles: CycleCounter,
+    // Rumble motor
+    rumble: RumbleSignal,
+    // Recent frames for rewinding, None when disabled
+    rewind: Option<rewind::RewindBuffer>,
+    // Set during run_m_cycle, the bus port only yields after every M-cycle then
+    m_cycle_steps: Rc<Cell<bool>>,
 }
 
 impl DancyHandle {
-    pub fn new(rom_bytes: Vec<u8>) -> Self {
-        let frame_ready = Rc::new(Cell::new(false));
-        let video_buffer = Rc::new(RefCell::new(vec![0; 160 * 144 * 4]));
+    pub fn new(rom_bytes: Vec<u8>) -> Result<Self, cartridge::CartridgeError> {
+        Self::build(rom_bytes, None)
+    }
 
-        // Initialize Bus with signal
-        let bus = mmu::Bus::new(rom_bytes, Rc::clone(&frame_ready), Rc::clone(&video_buffer));
+    // Run a DMG (256 bytes) or CGB (2304 bytes) boot ROM first instead of starting at 0x0100
+    pub fn with_boot_rom(rom_bytes: Vec<u8>, boot_rom: Vec<u8>) -> Result<Self, cartridge::CartridgeError> {
+        Self::build(rom_bytes, Some(boot_rom))
+    }
 
-        let state = Rc::new(RefCell::new(EmulatorState {
-            cpu: cpu::Cpu::new(),
-            bus,
-        }));
+    #[allow(clippy::await_holding_refcell_ref)] // Only the CPU, the host waits for the instruction to end, see below
+    fn build(rom_bytes: Vec<u8>, boot_rom: Option<Vec<u8>>) -> Result<Self, cartridge::CartridgeError> {
+        let frame_ready = Rc::new(Cell::new(false));
+        let video_buffer = Rc::new(RefCell::new(vec![0xFF; 160 * 144 * 4])); // White, like the LCD while it's off
+        let audio_buffer = Rc::new(RefCell::new(io::apu::AudioBuffer::new()));
+
+        let cpu = match boot_rom {
+            Some(_) => cpu::Cpu::power_on(),
+            None => cpu::Cpu::new(),
+        };
 
-        let state_for_future = Rc::clone(&state);
+        // Initialize Bus with signal
+        let bus = mmu::Bus::new(
+            rom_bytes,
+            Rc::clone(&frame_ready),
+            Rc::clone(&video_buffer),
+            Rc::clone(&audio_buffer),
+            boot_rom,
+        )?;
+
+        let cycles = Rc::clone(&bus.cycles);
+        let rumble = Rc::clone(&bus.rumble);
+
+        let cpu = Rc::new(RefCell::new(cpu));
+        let bus = Rc::new(RefCell::new(bus));
+
+        let cpu_for_future = Rc::clone(&cpu);
+        let m_cycle_steps = Rc::new(Cell::new(false));
+        let port = mmu::BusPort::new(Rc::clone(&bus), Rc::clone(&m_cycle_steps));
         let execution_future = Box::pin(async move {
             loop {
-                // Borrow lives until instruction yields
-                let mut s = state_for_future.borrow_mut();
-
-                // SPLIT BORROW: see cpu and bus as separate fields
-                let EmulatorState {
-                    ref mut cpu,
-                    ref mut bus,
-                } = *s;
-
-                cpu.step(bus).await;
-
-                // lock dropped!
+                {
+                    // Borrow lives for one instruction. In run_m_cycle the port yields after every
+                    // M-cycle with the Bus released, so only the CPU is off limits mid-instruction.
+                    let mut cpu = cpu_for_future.borrow_mut();
+                    cpu.step(&port).await;
+                }
+                // And once more between instructions, so the host can tell where they end
+                Yield(false).await;
             }
         });
 
-        Self {
-            state,
+        Ok(Self {
+            cpu,
+            bus,
             execution_future,
             frame_ready,
             video_buffer,
-        }
+            audio_buffer,
+            cycles,
+            rumble,
+            rewind: None,
+            m_cycle_steps,
+        })
+    }
+
+    // Start with battery-backed RAM (.sav bytes) from an earlier session
+    pub fn with_save_data(rom_bytes: Vec<u8>, save_data: Vec<u8>) -> Result<Self, cartridge::CartridgeError> {
+        let handle = Self::new(rom_bytes)?;
+        handle.load_save_data(&save_data);
+        Ok(handle)
+    }
+
+    // Replace battery-backed RAM with .sav bytes, best done before the first frame
+    pub fn load_save_data(&self, save_data: &[u8]) {
+        self.bus.borrow_mut().cartridge.load_save_data(save_data);
     }
 
     // FFI should call this 60 times a second (or however many fps you want)
@@ -76,30 +127,220 @@ impl DancyHandle {
         let waker = dummy_waker();
         let mut cx = Context::from_waker(&waker);
 
+        // Drive the CPU future
+        while self.execution_future.as_mut().poll(&mut cx).is_pending() {
+            // Is frame done? Finish the instruction first
+            if self.frame_ready.get() && self.at_instruction_boundary() {
+                self.frame_ready.set(false); // Reset signal
+                self.record_frame();
+                break; // Return to FFI
+            }
+        }
+    }
+
+    // Alternative to run_frame for frontends paced by the audio device (the DMG runs at ~59.73 fps).
+    // Runs for `t_cycles` T-cycles, returns true if a frame was completed in the meantime.
+    pub fn run_cycles(&mut self, t_cycles: u64) -> bool {
+        let waker = dummy_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        // Stops on the first instruction boundary past the target
+        let target = self.cycles.get() + t_cycles;
+        let mut frame_done = false;
+        while !(self.cycles.get() >= target && self.at_instruction_boundary())
+            && self.execution_future.as_mut().poll(&mut cx).is_pending()
+        {
+            if self.frame_ready.get() && self.at_instruction_boundary() {
+                self.frame_ready.set(false);
+                self.record_frame();
+                frame_done = true;
+            }
+        }
+        frame_done
+    }
+
+    // Run until `samples` more stereo samples have been mixed, returns true if a frame was completed
+    pub fn run_samples(&mut self, samples: u32) -> bool {
+        let target = self.audio_buffer.borrow().produced + samples as u64;
+        let mut frame_done = false;
         loop {
-            // Drive the CPU future
-            match self.execution_future.as_mut().poll(&mut cx) {
-                Poll::Pending => {
-                    // Is frame done?
-                    if self.frame_ready.get() {
-                        self.frame_ready.set(false); // Reset signal
-                        break; // Return to FFI
-                    }
-                }
-                Poll::Ready(_) => break,
+            let (produced, sample_rate) = {
+                let buffer = self.audio_buffer.borrow();
+                (buffer.produced, buffer.sample_rate as u64)
+            };
+            if produced >= target {
+                return frame_done;
             }
+            // Resampling isn't an exact multiple of the clock, go again if this falls short
+            let t_cycles = ((target - produced) * mmu::CLOCK_RATE as u64).div_ceil(sample_rate);
+            frame_done |= self.run_cycles(t_cycles);
         }
     }
 
+    // T-cycles since power on
+    pub fn cycles(&self) -> u64 {
+        self.cycles.get()
+    }
+
+    // Run a single M-cycle, which may leave the CPU in the middle of an instruction.
+    // The bus can be inspected and changed in between, run_frame etc. finish the instruction.
+    pub fn run_m_cycle(&mut self) {
+        let waker = dummy_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        // The yield between instructions doesn't advance the clock. A finished frame is
+        // left signalled, the next run_frame returns at the end of the instruction.
+        let target = self.cycles.get() + 4;
+        self.m_cycle_steps.set(true);
+        while self.cycles.get() < target && self.execution_future.as_mut().poll(&mut cx).is_pending() {}
+        self.m_cycle_steps.set(false);
+    }
+
+    // Only in between instructions, the CPU is busy otherwise
+    pub fn at_instruction_boundary(&self) -> bool {
+        self.cpu.try_borrow_mut().is_ok()
+    }
+
+    // Run the rest of the current instruction, if run_m_cycle stopped in the middle of one
+    fn finish_instruction(&mut self) {
+        let waker = dummy_waker();
+        let mut cx = Context::from_waker(&waker);
+        while !self.at_instruction_boundary() && self.execution_future.as_mut().poll(&mut cx).is_pending() {}
+    }
+
+    // Registers etc., runs the rest of the current instruction first if run_m_cycle stopped in it
+    pub fn cpu(&mut self) -> Ref<'_, cpu::Cpu> {
+        self.finish_instruction();
+        self.cpu.borrow()
+    }
+
+    // Memory and hardware, available between any two M-cycles
+    pub fn bus(&self) -> Ref<'_, mmu::Bus> {
+        self.bus.borrow()
+    }
+
+    pub fn bus_mut(&self) -> RefMut<'_, mmu::Bus> {
+        self.bus.borrow_mut()
+    }
+
     // Get buffer
     pub fn get_graphics(&self) -> Vec<u8> {
         let buffer = self.video_buffer.borrow();
         buffer.to_vec()
     }
 
+    // Get interleaved stereo samples (L, R, L, R...) produced since the last call
+    pub fn get_audio_samples(&self) -> Vec<i16> {
+        std::mem::take(&mut self.audio_buffer.borrow_mut().samples)
+    }
+
+    // Output rate for get_audio_samples, 48000 Hz by default. Clamped to 8000-192000 Hz.
+    pub fn set_sample_rate(&self, sample_rate: u32) {
+        let rates = io::apu::SAMPLE_RATES;
+        let mut buffer = self.audio_buffer.borrow_mut();
+        buffer.sample_rate = sample_rate.clamp(*rates.start(), *rates.end());
+        buffer.samples.clear();
+    }
+
+    // Bytes for the .sav file, empty if the cartridge has no battery
+    pub fn get_save_data(&self) -> Vec<u8> {
+        self.bus.borrow_mut().cartridge.save_data()
+    }
+
+    // Has save RAM been written since the last get_save_data?
+    pub fn is_save_dirty(&self) -> bool {
+        self.bus.borrow().cartridge.is_save_dirty()
+    }
+
+    // Snapshot of the whole machine, tagged with the cartridge's checksums
+    pub fn save_state(&mut self) -> Vec<u8> {
+        self.finish_instruction();
+        let bus = self.bus.borrow();
+        let header = &bus.cartridge.header;
+        let mut state = savestate::StateWriter::new(header.header_checksum, header.global_checksum);
+        self.cpu.borrow().save_state(&mut state);
+        bus.save_state(&mut state);
+        state.finish()
+    }
+
+    // Restore a save_state snapshot. Nothing changes if it's rejected.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), savestate::StateError> {
+        let backup = self.save_state();
+        self.restore_state(data).inspect_err(|_| {
+            self.restore_state(&backup).expect("Backup state failed to load");
+        })
+    }
+
+    fn restore_state(&self, data: &[u8]) -> Result<(), savestate::StateError> {
+        let mut bus = self.bus.borrow_mut();
+        let header = &bus.cartridge.header;
+        let mut state = savestate::StateReader::new(data, header.header_checksum, header.global_checksum)?;
+        self.cpu.borrow_mut().load_state(&mut state)?;
+        bus.load_state(&mut state)?;
+        state.finish()
+    }
+
+    // Keep a save state of each of the last `seconds` seconds of frames, 0 disables rewinding.
+    // Changing it drops the history.
+    pub fn set_rewind_seconds(&mut self, seconds: u32) {
+        self.rewind = (seconds > 0).then(|| rewind::RewindBuffer::new(seconds));
+    }
+
+    // Step one frame back in the rewind history and return it. Stays on the oldest frame once it runs out.
+    pub fn rewind_frame(&mut self) -> Vec<u8> {
+        self.finish_instruction();
+        if let Some(state) = self.rewind.as_mut().and_then(|rewind| rewind.pop()) {
+            self.restore_state(&state).expect("Rewind state failed to load");
+        }
+        self.get_graphics()
+    }
+
+    fn record_frame(&mut self) {
+        if self.rewind.is_none() {
+            return;
+        }
+        let state = self.save_state();
+        if let Some(rewind) = &mut self.rewind {
+            rewind.push(state);
+        }
+    }
+
+    // Bytes the game sent over the link cable since the last call, test ROMs print their results there.
+    // Nothing is kept before the first call, so frontends that never read it don't collect output forever.
+    pub fn take_serial_output(&self) -> String {
+        std::mem::take(self.bus.borrow_mut().serial.log_buffer.get_or_insert_default())
+    }
+
+    // Write the registers before every instruction to `path`, up to `max_lines` lines. Off by default.
+    pub fn log_cpu(&mut self, path: &str, max_lines: u64) -> std::io::Result<()> {
+        self.finish_instruction();
+        self.cpu.borrow_mut().logger = Some(cpu::Logger::new(path, max_lines)?);
+        Ok(())
+    }
+
+    // Is the rumble motor of an MBC5 rumble cart running?
+    pub fn get_rumble(&self) -> bool {
+        self.rumble.get()
+    }
+
     // Update joypad state. 0 = pressed, `↓ ↑ ← → S s B A` as u8
     pub fn update_buttons(&mut self, pressed: u8) {
-        self.state.borrow_mut().bus.joypad.set_buttons(pressed);
+        self.bus.borrow_mut().joypad.set_buttons(pressed);
+    }
+}
+
+// Pending once, gives control back to whoever polls the execution loop
+pub(crate) struct Yield(pub(crate) bool);
+impl Future for Yield {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
     }
 }
 
@@ -115,9 +356,63 @@ fn dummy_waker() -> Waker {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    // Blargg's ROMs print "Passed" or "Failed" over serial or on screen. Mooneye's load
+    // B, C, D, E, H, L with 3, 5, 8, 13, 21, 34 (or all 0x42 on failure) and run LD B, B.
+    // Ok with what the ROM printed, Err with the output if it failed or never finished.
+    fn run_test_rom(rom: Vec<u8>, max_frames: u32) -> Result<String, String> {
+        let mut handle = DancyHandle::new(rom).unwrap();
+        let mut serial = handle.take_serial_output(); // Start collecting
+        for _ in 0..max_frames {
+            handle.run_frame();
+            serial.push_str(&handle.take_serial_output());
+
+            if std::mem::take(&mut handle.cpu.borrow_mut().breakpoint) {
+                let r = &handle.cpu().registers;
+                match [r.b, r.c, r.d, r.e, r.h, r.l] {
+                    [3, 5, 8, 13, 21, 34] => return Ok(serial),
+                    [0x42, 0x42, 0x42, 0x42, 0x42, 0x42] => return Err(format!("Registers are 0x42\n{}", serial)),
+                    _ => {} // Just an LD B, B
+                }
+            }
+
+            // Some only print to the screen
+            for output in [serial.clone(), screen_text(&handle)] {
+                if output.contains("Passed") {
+                    return Ok(output);
+                }
+                if output.contains("Failed") {
+                    return Err(output);
+                }
+            }
+        }
+        Err(format!("No result after {} frames\n{}\n{}", max_frames, serial, screen_text(&handle)))
+    }
+
+    // Blargg's console uses ASCII codes as tile numbers
+    fn screen_text(handle: &DancyHandle) -> String {
+        let bus = handle.bus();
+        let rows: Vec<String> = (0..18)
+            .map(|row| {
+                let line: String = (0..20)
+                    .map(|col| bus.ppu.unblocked_read_vram(0x9800 + row * 32 + col) as char)
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        rows.join("\n")
+    }
+
+    fn assert_test_rom(path: &str, max_frames: u32) {
+        let rom = std::fs::read(path).unwrap_or_else(|e| panic!("Cannot read {}: {}", path, e));
+        if let Err(output) = run_test_rom(rom, max_frames) {
+            panic!("{} failed:\n{}", path, output);
+        }
+    }
+
     #[test]
     fn test_cpu_instructions() {
-        const TEST_ROMS: [&'static str; 11] = [
+        const TEST_ROMS: [&str; 11] = [
             "01-special",
             "02-interrupts",
             "03-op sp,hl",
@@ -131,71 +426,319 @@ mod tests {
             "11-op a,(hl)",
         ];
         for rom_path in &TEST_ROMS {
-            let full_rom_path = format!("test_roms/cpu_instrs/{}.gb", rom_path);
-            let rom = std::fs::read(full_rom_path).unwrap();
-            let mut handle = DancyHandle::new(rom);
-
-            for _ in 0..1500 {
-                handle.run_frame();
-            }
-            println!("-------------");
+            assert_test_rom(&format!("test_roms/cpu_instrs/{}.gb", rom_path), 1500);
         }
     }
 
     #[test]
     fn test_instruction_timing() {
+        assert_test_rom("test_roms/instr_timing.gb", 1500);
+    }
 
-        let rom = std::fs::read("test_roms/instr_timing.gb").unwrap();
-        let mut handle = DancyHandle::new(rom);
+    #[test]
+    fn test_memory_timing() {
+        assert_test_rom("test_roms/mem_timing.gb", 1500);
+    }
 
-        for _ in 0..1500 {
-            handle.run_frame();
+    #[test]
+    fn test_memory_timing_two() {
+        assert_test_rom("test_roms/mem_timing-2.gb", 100000);
+    }
+
+    #[test]
+    fn test_halt_bug() {
+        assert_test_rom("test_roms/halt_bug.gb", 10000);
+    }
+
+    // Blargg's dmg_sound singles, not vendored yet. Copy rom_singles from https://github.com/retrio/gb-test-roms
+    // to test_roms/dmg_sound. They print the result on screen, 02 and 03 check DIV-clocked length counters.
+    #[test]
+    #[ignore = "needs the dmg_sound ROMs in test_roms/dmg_sound"]
+    fn test_dmg_sound() {
+        const TEST_ROMS: [&str; 3] = ["01-registers", "02-len ctr", "03-trigger"];
+        for rom_path in &TEST_ROMS {
+            assert_test_rom(&format!("test_roms/dmg_sound/{}.gb", rom_path), 3000);
         }
     }
 
+    // Mooneye's acceptance/ppu ROMs for mode 3 length and STAT timing, not vendored yet.
+    // Build them from https://github.com/Gekkio/mooneye-test-suite and copy them to test_roms/mooneye/ppu.
     #[test]
-    fn test_memory_timing() {
+    #[ignore = "needs the mooneye ppu ROMs in test_roms/mooneye/ppu"]
+    fn test_mooneye_ppu_timing() {
+        const TEST_ROMS: [&str; 6] = [
+            "hblank_ly_scx_timing-GS",
+            "intr_1_2_timing-GS",
+            "intr_2_0_timing",
+            "intr_2_mode0_timing",
+            "intr_2_mode3_timing",
+            "intr_2_mode0_timing_sprites",
+        ];
+        for rom_path in &TEST_ROMS {
+            assert_test_rom(&format!("test_roms/mooneye/ppu/{}.gb", rom_path), 600);
+        }
+    }
+
+    #[test]
+    fn test_mooneye_registers() {
+        // JP 0x0150, then LD B, 3 ... LD L, 34, LD B, B, JR -2
+        let rom = |values: [u8; 6]| {
+            let mut rom = vec![0; 0x8000];
+            rom[0x0100..0x0103].copy_from_slice(&[0xC3, 0x50, 0x01]);
+            for (i, (opcode, value)) in [0x06, 0x0E, 0x16, 0x1E, 0x26, 0x2E].into_iter().zip(values).enumerate() {
+                rom[0x0150 + i * 2..0x0152 + i * 2].copy_from_slice(&[opcode, value]);
+            }
+            rom[0x015C..0x015F].copy_from_slice(&[0x40, 0x18, 0xFE]);
+            rom[0x014D] = cartridge::header::header_checksum_of(&rom);
+            rom
+        };
+        assert_eq!(run_test_rom(rom([3, 5, 8, 13, 21, 34]), 10), Ok(String::new()));
+        assert!(run_test_rom(rom([0x42; 6]), 10).is_err());
+        assert!(run_test_rom(rom([1, 2, 3, 4, 5, 6]), 10).unwrap_err().starts_with("No result after 10 frames"));
+    }
+
+    #[test]
+    fn test_run_cycles_and_samples() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::new(rom).unwrap();
+
+        // One frame is 70224 T-cycles
+        assert!(handle.run_cycles(70224 * 2));
+        let cycles = handle.cycles();
+        // Interrupt dispatch and the longest instruction
+        assert!((70224 * 2..70224 * 2 + 44).contains(&cycles), "{}", cycles);
+        assert!(!handle.run_cycles(1000));
+        assert!((1000..1044).contains(&(handle.cycles() - cycles)));
+
+        handle.get_audio_samples();
+        handle.run_samples(800);
+        assert_eq!(handle.get_audio_samples().len(), 800 * 2);
+        handle.set_sample_rate(44100);
+        handle.run_samples(441);
+        assert_eq!(handle.get_audio_samples().len(), 441 * 2);
+
+        // run_frame still stops at the next frame
+        handle.run_frame();
+        handle.run_frame();
+        assert!((handle.cycles() - cycles) / 70224 <= 2);
+
+        // Clamped instead of dividing by zero
+        handle.set_sample_rate(0);
+        handle.get_audio_samples();
+        handle.run_samples(80);
+        assert_eq!(handle.get_audio_samples().len(), 80 * 2);
+    }
+
+    #[test]
+    fn test_state_access_between_frames() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::with_save_data(rom, vec![0xFF; 0x2000]).unwrap();
+        handle.run_frame();
+        handle.update_buttons(0xF7);
+        handle.run_frame();
+        // Tetris has no battery
+        assert!(!handle.is_save_dirty());
+        assert!(handle.get_save_data().is_empty());
+    }
+
+    #[test]
+    fn test_boot_rom_unmaps_on_ff50_write() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        assert_eq!(
+            DancyHandle::with_boot_rom(rom.clone(), vec![0; 0x0200]).err(),
+            Some(cartridge::CartridgeError::BadBootRomSize)
+        );
+
+        // NOPs, then LD A, 1 and LDH (0x50), A in the last four bytes like the real one
+        let mut boot_rom = vec![0x00; 0x0100];
+        boot_rom[0x00FC..].copy_from_slice(&[0x3E, 0x01, 0xE0, 0x50]);
+        let mut handle = DancyHandle::with_boot_rom(rom.clone(), boot_rom).unwrap();
+        assert_eq!(handle.cpu().pc, 0x0000);
+        assert_eq!(handle.bus().raw_read(0x00FC), 0x3E);
+        assert_eq!(handle.bus().raw_read(0x0100), rom[0x0100]);
+        // LCD starts off
+        assert_eq!(handle.bus().raw_read(0xFF40), 0x00);
+
+        handle.run_cycles(0x0110 * 4);
+        assert!(handle.cpu().pc >= 0x0100);
+        assert_eq!(handle.bus().raw_read(0x00FC), rom[0x00FC]);
+    }
 
-        let rom = std::fs::read("test_roms/mem_timing.gb").unwrap();
-        let mut handle = DancyHandle::new(rom);
+    #[test]
+    fn test_bus_access_between_m_cycles() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::new(rom).unwrap();
+        handle.run_frame();
+
+        let mut mid_instruction = false;
+        let mut lines = Vec::new();
+        for _ in 0..300 {
+            let cycles = handle.cycles();
+            handle.run_m_cycle();
+            assert_eq!(handle.cycles(), cycles + 4);
+            mid_instruction |= !handle.at_instruction_boundary();
+
+            // LY moves on every 114 M-cycles
+            let ly = handle.bus().raw_read(0xFF44);
+            if lines.last() != Some(&ly) {
+                lines.push(ly);
+            }
+            handle.update_buttons(0xFF);
+            handle.bus_mut().joypad.set_buttons(0xFF);
+        }
+        assert!(mid_instruction);
+        assert!(lines.len() >= 3, "{:?}", lines);
+
+        // Save states and the CPU wait for the instruction to end
+        handle.save_state();
+        assert!(handle.at_instruction_boundary());
+        while handle.at_instruction_boundary() {
+            handle.run_m_cycle();
+        }
+        let _ = handle.cpu().pc;
+        assert!(handle.at_instruction_boundary());
+    }
 
-        for _ in 0..1500 {
+    #[test]
+    fn test_serial_output_is_opt_in() {
+        let rom = std::fs::read("test_roms/cpu_instrs/01-special.gb").unwrap();
+        let mut handle = DancyHandle::new(rom.clone()).unwrap();
+        for _ in 0..30 {
             handle.run_frame();
         }
+        assert!(handle.bus().serial.log_buffer.is_none());
+        assert_eq!(handle.take_serial_output(), "");
+
+        let mut collecting = DancyHandle::new(rom).unwrap();
+        assert_eq!(collecting.take_serial_output(), "");
+        for _ in 0..30 {
+            collecting.run_frame();
+        }
+        assert!(collecting.take_serial_output().starts_with("01-special"));
     }
 
     #[test]
-    fn test_memory_timing_two() {
+    fn test_save_state_round_trip() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::new(rom).unwrap();
+        for _ in 0..120 {
+            handle.run_frame();
+        }
+        let (saved_cycles, state) = (handle.cycles(), handle.save_state());
 
-        let rom = std::fs::read("test_roms/mem_timing-2.gb").unwrap();
-        let mut handle = DancyHandle::new(rom);
+        handle.update_buttons(0xF7);
+        for _ in 0..30 {
+            handle.run_frame();
+        }
+        let (cycles, frame) = (handle.cycles(), handle.get_graphics());
 
-        for _ in 0..100000 {
+        handle.load_state(&state).unwrap();
+        assert_eq!(handle.save_state(), state);
+        assert_eq!(handle.cycles(), saved_cycles);
+        for _ in 0..30 {
             handle.run_frame();
         }
+        assert_eq!(handle.cycles(), cycles);
+        assert_eq!(handle.get_graphics(), frame);
+
+        // Rejected states leave the emulator alone
+        let current = handle.save_state();
+        assert_eq!(
+            handle.load_state(&state[..state.len() / 2]),
+            Err(savestate::StateError::Corrupt)
+        );
+        assert_eq!(handle.save_state(), current);
+
+        let other = std::fs::read("test_roms/cpu_instrs/01-special.gb").unwrap();
+        let mut other = DancyHandle::new(other).unwrap();
+        assert_eq!(other.load_state(&state), Err(savestate::StateError::WrongRom));
     }
 
     #[test]
-    fn test_halt_bug() {
-        // Initialize env_logger
+    fn test_rewind() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::new(rom).unwrap();
+        handle.set_rewind_seconds(1);
+        for _ in 0..100 {
+            handle.run_frame();
+        }
+        let (state, frame) = (handle.save_state(), handle.get_graphics());
 
-        let rom = std::fs::read("test_roms/halt_bug.gb").unwrap();
-        let mut handle = DancyHandle::new(rom);
+        handle.update_buttons(0xF7);
+        for _ in 0..40 {
+            handle.run_frame();
+        }
+        for _ in 0..39 {
+            handle.rewind_frame();
+        }
+        assert_eq!(handle.rewind_frame(), frame);
+        assert_eq!(handle.save_state(), state);
+    }
 
-        for _ in 0..10000 {
+    #[test]
+    fn test_rewind_mid_instruction() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::new(rom).unwrap();
+        handle.set_rewind_seconds(1);
+        for _ in 0..10 {
             handle.run_frame();
         }
+        let state = handle.save_state();
+        handle.run_frame();
+
+        // Stop inside an instruction, the CPU is still borrowed by it
+        while handle.at_instruction_boundary() {
+            handle.run_m_cycle();
+        }
+        handle.rewind_frame();
+        assert_eq!(handle.save_state(), state);
     }
 
+    // cargo test --release bench_tetris_fps -- --ignored --nocapture
     #[test]
-    fn test_tetris() {
-        // Initialize env_logger
+    #[ignore]
+    fn bench_tetris_fps() {
+        let rom = std::fs::read("test_roms/tetris.gb").unwrap();
+        let mut handle = DancyHandle::new(rom).unwrap();
+        // Past the boot screen
+        for _ in 0..300 {
+            handle.run_frame();
+        }
 
+        let start = std::time::Instant::now();
+        for _ in 0..3600 {
+            handle.run_frame();
+        }
+        let elapsed = start.elapsed();
+        println!("3600 frames in {:.2?}, {:.0} fps", elapsed, 3600.0 / elapsed.as_secs_f64());
+    }
+
+    #[test]
+    fn test_tetris() {
         let rom = std::fs::read("test_roms/tetris.gb").unwrap();
-        let mut handle = DancyHandle::new(rom);
+        let mut handle = DancyHandle::new(rom).unwrap();
 
-        for _ in 0..10000 {
+        // Press start every 200 frames through the title, game type and music menus, then let pieces fall
+        let mut states = Vec::new();
+        for frame in 0..3000 {
+            let start = (400..1100).contains(&frame) && frame % 200 < 6;
+            handle.update_buttons(if start { 0xF7 } else { 0xFF });
             handle.run_frame();
+
+            // Game state in HRAM, 0x07 title, 0x0E game type, 0x11 music type, 0x00 playing
+            let state = handle.bus().debug_read(0xFFE1);
+            if states.last() != Some(&state) {
+                states.push(state);
+            }
         }
+        let expected = [0x00, 0x24, 0x25, 0x35, 0x06, 0x07, 0x08, 0x0E, 0x10, 0x11, 0x0A, 0x00];
+        assert_eq!(states, expected);
+
+        // Digits are tiles 0x00-0x09 and letters follow, 0x2F is blank
+        let bus = handle.bus();
+        let tile = |row: u16, col: u16| bus.ppu.unblocked_read_vram(0x9800 + row * 32 + col);
+        let score: Vec<u8> = (14..19).map(|col| tile(1, col)).collect();
+        assert_eq!(score, [0x1C, 0x0C, 0x18, 0x1B, 0x0E]); // SCORE
+        assert!((2..12).any(|col| tile(17, col) != 0x2F), "No pieces landed");
     }
 }
@@ -1,30 +1,19 @@
-use crate::{FrameSignal, SharedFrameBuffer};
-use std::pin::Pin;
-use std::task::Context;
-use std::task::Poll;
-use std::time::Instant;
+use crate::{CycleCounter, FrameSignal, RumbleSignal, SharedAudioBuffer, SharedFrameBuffer, Yield};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
-use crate::cartridge::Cartridge;
+use crate::cartridge::{Cartridge, CartridgeError};
 use crate::io::apu::Apu;
 use crate::io::joypad::Joypad;
 use crate::io::{ppu::Ppu, serial::Serial, timer::Timer};
+use crate::savestate::{StateError, StateReader, StateWriter};
 
-struct Yield(bool);
-impl Future for Yield {
-    type Output = ();
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        if self.0 {
-            Poll::Ready(())
-        } else {
-            self.0 = true;
-            cx.waker().wake_by_ref();
-            Poll::Pending
-        }
-    }
-}
+// T-cycles per second
+pub const CLOCK_RATE: u32 = 4194304;
 
 pub struct Bus {
-    cartridge: Cartridge,
+    pub cartridge: Cartridge,
+    boot_rom: Option<Vec<u8>>, // Mapped over the cartridge until 0xFF50 is written
     wram: [u8; 8192],
     hram: [u8; 127],
 
@@ -44,17 +33,37 @@ pub struct Bus {
     pub interrupt_enable: u8, // 0xFFFF
 
     pub frame_ready: FrameSignal,
+
+    pub cycles: CycleCounter, // T-cycles since power on
+
+    pub rumble: RumbleSignal, // Motor state of MBC5 rumble carts
 }
 
 impl Bus {
-    pub fn new(rom_data: Vec<u8>, frame_ready: FrameSignal, video_buffer: SharedFrameBuffer) -> Self {
-        Bus {
-            cartridge: Cartridge::new(rom_data),
+    pub fn new(
+        rom_data: Vec<u8>,
+        frame_ready: FrameSignal,
+        video_buffer: SharedFrameBuffer,
+        audio_buffer: SharedAudioBuffer,
+        boot_rom: Option<Vec<u8>>,
+    ) -> Result<Self, CartridgeError> {
+        if boot_rom.as_ref().is_some_and(|boot_rom| !matches!(boot_rom.len(), 0x0100 | 0x0900)) {
+            return Err(CartridgeError::BadBootRomSize);
+        }
+        let rumble = Rc::new(Cell::new(false));
+        // Without a boot ROM, start in the state it leaves behind
+        let (ppu, apu) = match boot_rom {
+            Some(_) => (Ppu::power_on(video_buffer), Apu::power_on(audio_buffer)),
+            None => (Ppu::new(video_buffer), Apu::new(audio_buffer)),
+        };
+        Ok(Bus {
+            cartridge: Cartridge::new(rom_data, Rc::clone(&rumble))?,
+            boot_rom,
             wram: [0; 8192],
             hram: [0; 127],
 
-            ppu: Ppu::new(video_buffer),
-            apu: Apu::new(),
+            ppu,
+            apu,
 
             timer: Timer::new(),
             joypad: Joypad::new(),
@@ -69,18 +78,78 @@ impl Bus {
             interrupt_enable: 0x00,
 
             frame_ready,
+
+            cycles: Rc::new(Cell::new(0)),
+
+            rumble,
+        })
+    }
+
+    // Only valid between instructions, nothing mid-instruction lives outside the components
+    pub fn save_state(&self, state: &mut StateWriter) {
+        self.cartridge.save_state(state);
+        state.vec(self.boot_rom.as_deref().unwrap_or_default());
+        state.bytes(&self.wram);
+        state.bytes(&self.hram);
+
+        self.ppu.save_state(state);
+        self.apu.save_state(state);
+        self.timer.save_state(state);
+        self.joypad.save_state(state);
+        self.serial.save_state(state);
+
+        state.bool(self.dma_active);
+        state.u8(self.dma_base);
+        state.u8(self.dma_byte);
+        state.u8(self.dma_delay);
+
+        state.u8(self.interrupt_flag);
+        state.u8(self.interrupt_enable);
+        state.u64(self.cycles.get());
+    }
+
+    pub fn load_state(&mut self, state: &mut StateReader) -> Result<(), StateError> {
+        self.cartridge.load_state(state)?;
+        let boot_rom = state.vec()?;
+        self.boot_rom = (!boot_rom.is_empty()).then_some(boot_rom);
+        state.bytes(&mut self.wram)?;
+        state.bytes(&mut self.hram)?;
+
+        self.ppu.load_state(state)?;
+        self.apu.load_state(state)?;
+        self.timer.load_state(state)?;
+        self.joypad.load_state(state)?;
+        self.serial.load_state(state)?;
+
+        self.dma_active = state.bool()?;
+        self.dma_base = state.u8()?;
+        self.dma_byte = state.u8()?;
+        self.dma_delay = state.u8()?;
+        if (self.dma_active && self.dma_byte >= 160) || self.dma_delay > 2 {
+            return Err(StateError::Corrupt);
         }
+
+        self.interrupt_flag = state.u8()?;
+        self.interrupt_enable = state.u8()?;
+        self.cycles.set(state.u64()?);
+        Ok(())
     }
 
-    pub async fn tick(&mut self) {
+    // One M-cycle of everything but the CPU
+    pub fn tick(&mut self) {
+        self.cycles.set(self.cycles.get() + 4);
+
         // DMA
         if self.dma_active {
             if self.dma_delay > 0 {
                 self.dma_delay -= 1;
             } else {
                 let src_addr = ((self.dma_base as u16) << 8) + (self.dma_byte as u16);
-                let byte = self.unblocked_raw_read(src_addr);
-                self.ppu.write_oam(0xFE00 + (self.dma_byte as u16), byte);
+                let byte = match src_addr {
+                    0x8000..=0x9FFF => self.ppu.unblocked_read_vram(src_addr),
+                    _ => self.unblocked_raw_read(src_addr),
+                };
+                self.ppu.unblocked_write_oam(0xFE00 + (self.dma_byte as u16), byte);
                 self.dma_byte = self.dma_byte.wrapping_add(1);
                 if self.dma_byte >= 160 {
                     self.dma_active = false;
@@ -92,61 +161,57 @@ impl Bus {
         let (v_blank, lcd_stat) = self.ppu.step(4);
         if v_blank {
             self.interrupt_flag |= 0b0000_0001;
-
-            self.frame_ready.set(true); // Signal frame ready
-            Yield(false).await;
+        }
+        if self.ppu.take_frame_done() {
+            self.frame_ready.set(true); // Signal frame ready, the CPU yields after this instruction
         }
         if lcd_stat {
             self.interrupt_flag |= 0b0000_0010;
         }
+        self.apu.step(4);
         if self.timer.step(4) {
             self.interrupt_flag |= 0b0000_0100;
         }
+        if self.timer.take_frame_sequencer_clock() {
+            self.apu.clock_frame_sequencer();
+        }
         if self.serial.step(4) {
             self.interrupt_flag |= 0b0000_1000;
         }
+        self.cartridge.step(4);
     }
 
-    pub async fn read(&mut self, addr: u16) -> u8 {
+    pub fn read(&mut self, addr: u16) -> u8 {
         let value = self.raw_read(addr);
-        self.tick().await;
+        self.tick();
         value
     }
 
-    pub async fn write(&mut self, addr: u16, value: u8) {
+    pub fn write(&mut self, addr: u16, value: u8) {
         self.raw_write(addr, value);
-        self.tick().await;
-    }
-
-    pub async fn read_u16(&mut self, addr: u16) -> u16 {
-        let lo = self.read(addr).await;
-        let hi = self.read(addr.wrapping_add(1)).await;
-        u16::from_le_bytes([lo, hi])
+        self.tick();
     }
 
-    pub async fn write_u16(&mut self, addr: u16, value: u16, le: bool) {
-        let [lo, hi] = value.to_le_bytes();
-        if le {
-            // LD (nn), SP
-            self.write(addr, lo).await;
-            self.write(addr.wrapping_add(1), hi).await;
-        } else {
-            // Everything else (stack operation)
-            self.write(addr.wrapping_add(1), hi).await;
-            self.write(addr, lo).await;
+    pub fn raw_read(&self, addr: u16) -> u8 {
+        if self.dma_active && !(0xFF80..=0xFFFE).contains(&addr) {
+            return 0xFF;
         }
+        self.unblocked_raw_read(addr)
     }
 
-    pub fn raw_read(&self, addr: u16) -> u8 {
-        if self.dma_active {
-            if addr < 0xFF80 || addr > 0xFFFE {
-                return 0xFF;
-            }
+    // What memory holds, regardless of DMA or the PPU mode. For tools, the CPU never sees this.
+    pub fn debug_read(&self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0x9FFF => self.ppu.unblocked_read_vram(addr),
+            0xFE00..=0xFE9F => self.ppu.unblocked_read_oam(addr),
+            _ => self.unblocked_raw_read(addr),
         }
-        self.unblocked_raw_read(addr)
     }
 
-    fn unblocked_raw_read(&self, addr: u16) -> u8 {
+    pub fn unblocked_raw_read(&self, addr: u16) -> u8 {
+        if let Some(byte) = self.read_boot_rom(addr) {
+            return byte;
+        }
         match addr {
             // ROM bank 00
             0x0000..=0x3FFF => self.cartridge.read(addr),
@@ -174,10 +239,8 @@ impl Bus {
     }
 
     pub fn raw_write(&mut self, addr: u16, value: u8) {
-        if self.dma_active {
-            if addr < 0xFF80 || addr > 0xFFFE {
-                return;
-            }
+        if self.dma_active && !(0xFF80..=0xFFFE).contains(&addr) {
+            return;
         }
         match addr {
             // Cartridge ROM
@@ -203,6 +266,15 @@ impl Bus {
         }
     }
 
+    // The CGB boot ROM leaves a gap for the cartridge header at 0x0100-0x01FF
+    fn read_boot_rom(&self, addr: u16) -> Option<u8> {
+        let boot_rom = self.boot_rom.as_ref()?;
+        match addr {
+            0x0000..=0x00FF | 0x0200..=0x08FF => boot_rom.get(addr as usize).copied(),
+            _ => None,
+        }
+    }
+
     fn read_io(&self, addr: u16) -> u8 {
         match addr {
             // Joypad Input
@@ -215,16 +287,20 @@ impl Bus {
             0xFF0F => self.interrupt_flag | 0xE0,
             // Audio
             0xFF10..=0xFF26 => self.apu.read(addr),
+            // Wave pattern RAM
+            0xFF30..=0xFF3F => self.apu.read(addr),
             // DMA
             0xFF46 => self.dma_base,
             // LCD Control, Status, Position, Scrolling, and Palettes
             0xFF40..=0xFF4B => self.ppu.read_register(addr),
+            // Boot ROM disable
+            0xFF50 => 0xFF,
             // CGB
             0xFF4C | 0xFF4D | 0xFF4F | 0xFF51..=0xFF55 | 0xFF56 | 0xFF68..=0xFF6B | 0xFF6C | 0xFF70 => {
                 self.read_cgb_io(addr)
             }
             _ => {
-                println!("Unimplemented read to IO address {:04X}", addr);
+                eprintln!("Unimplemented read to IO address {:04X}", addr);
                 0xFF
             }
         }
@@ -242,6 +318,8 @@ impl Bus {
             0xFF0F => self.interrupt_flag = value,
             // Audio
             0xFF10..=0xFF26 => self.apu.write(addr, value),
+            // Wave pattern RAM
+            0xFF30..=0xFF3F => self.apu.write(addr, value),
             // DMA
             0xFF46 => {
                 self.dma_active = true;
@@ -251,11 +329,17 @@ impl Bus {
             }
             // LCD Control, Status, Position, Scrolling, and Palettes
             0xFF40..=0xFF4B => self.ppu.write_register(addr, value),
+            // Boot ROM disable, any nonzero write unmaps it until reset
+            0xFF50 => {
+                if value != 0 {
+                    self.boot_rom = None;
+                }
+            }
             // CGB KEY1 Double Speed
             0xFF4C | 0xFF4D | 0xFF4F | 0xFF51..=0xFF55 | 0xFF56 | 0xFF68..=0xFF6B | 0xFF6C | 0xFF70 => {
                 self.write_cgb_io(addr, value)
             }
-            _ => println!("Unimplemented write to IO address {:04X}", addr),
+            _ => eprintln!("Unimplemented write to IO address {:04X}", addr),
         }
     }
 
@@ -267,3 +351,69 @@ impl Bus {
         // TODO: CGB
     }
 }
+
+// The CPU's side of the bus. Every access borrows the Bus for one M-cycle. While `m_cycle_steps` is set it
+// then yields, so the host can inspect or change the hardware between any two M-cycles. Otherwise the
+// instruction runs on, yielding on every M-cycle made run_frame ~8% slower.
+pub struct BusPort {
+    bus: Rc<RefCell<Bus>>,
+    m_cycle_steps: Rc<Cell<bool>>,
+}
+
+impl BusPort {
+    pub fn new(bus: Rc<RefCell<Bus>>, m_cycle_steps: Rc<Cell<bool>>) -> Self {
+        Self { bus, m_cycle_steps }
+    }
+
+    async fn end_m_cycle(&self) {
+        if self.m_cycle_steps.get() {
+            Yield(false).await;
+        }
+    }
+
+    pub async fn tick(&self) {
+        self.bus.borrow_mut().tick();
+        self.end_m_cycle().await;
+    }
+
+    pub async fn read(&self, addr: u16) -> u8 {
+        let value = self.bus.borrow_mut().read(addr);
+        self.end_m_cycle().await;
+        value
+    }
+
+    pub async fn write(&self, addr: u16, value: u8) {
+        self.bus.borrow_mut().write(addr, value);
+        self.end_m_cycle().await;
+    }
+
+    pub async fn read_u16(&self, addr: u16) -> u16 {
+        let lo = self.read(addr).await;
+        let hi = self.read(addr.wrapping_add(1)).await;
+        u16::from_le_bytes([lo, hi])
+    }
+
+    pub async fn write_u16(&self, addr: u16, value: u16, le: bool) {
+        let [lo, hi] = value.to_le_bytes();
+        if le {
+            // LD (nn), SP
+            self.write(addr, lo).await;
+            self.write(addr.wrapping_add(1), hi).await;
+        } else {
+            // Everything else (stack operation)
+            self.write(addr.wrapping_add(1), hi).await;
+            self.write(addr, lo).await;
+        }
+    }
+
+    // Requested and enabled interrupts, IE & IF
+    pub fn pending_interrupts(&self) -> u8 {
+        let bus = self.bus.borrow();
+        bus.interrupt_enable & bus.interrupt_flag & 0x1F
+    }
+
+    // Clear the IF bit of the interrupt being dispatched
+    pub fn acknowledge_interrupt(&self, mask: u8) {
+        self.bus.borrow_mut().interrupt_flag &= !mask;
+    }
+}
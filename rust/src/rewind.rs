@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+
+// A full snapshot every KEYFRAME_INTERVAL frames, the frames in between are stored as diffs against it
+pub const KEYFRAME_INTERVAL: usize = 30;
+const T_CYCLES_PER_FRAME: u64 = 70224;
+
+// One keyframe and the frames recorded after it
+struct Group {
+    keyframe: Vec<u8>,
+    deltas: Vec<Vec<u8>>,
+}
+impl Group {
+    fn len(&self) -> usize {
+        1 + self.deltas.len()
+    }
+}
+
+// Ring buffer of save states, one per frame, oldest dropped a whole group at a time.
+// The limit is a number of frames, not bytes. A Tetris state (DMG, no cartridge RAM) is 28,400 bytes, 11,520 of
+// them the two frame buffers packed to 2 bits a pixel.
+// Measured over 30 seconds of Tetris: about 1.1 KB per frame counting keyframes, 12,179 bytes for the largest
+// delta, so 10 seconds take around 650 KB.
+pub struct RewindBuffer {
+    capacity: usize,
+    groups: VecDeque<Group>,
+    frames: usize,
+    // Decoded keyframe of the last group, what new frames are diffed against
+    base: Vec<u8>,
+}
+
+impl RewindBuffer {
+    pub fn new(seconds: u32) -> Self {
+        Self {
+            capacity: (seconds as u64 * crate::mmu::CLOCK_RATE as u64 / T_CYCLES_PER_FRAME) as usize,
+            groups: VecDeque::new(),
+            frames: 0,
+            base: Vec::new(),
+        }
+    }
+
+    // Frames that can still be rewound to
+    pub fn len(&self) -> usize {
+        self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+
+    pub fn push(&mut self, state: Vec<u8>) {
+        match self.groups.back_mut() {
+            Some(group) if group.len() < KEYFRAME_INTERVAL => group.deltas.push(encode(&state, &self.base)),
+            _ => {
+                // Keyframes are stored the same way, against nothing
+                self.groups.push_back(Group {
+                    keyframe: encode(&state, &[]),
+                    deltas: Vec::new(),
+                });
+                self.base = state;
+            }
+        }
+        self.frames += 1;
+
+        // Keep at least `capacity` frames, so only drop the oldest group once the rest covers it
+        while let Some(front) = self.groups.front() {
+            if self.frames - front.len() < self.capacity {
+                break;
+            }
+            self.frames -= front.len();
+            self.groups.pop_front();
+        }
+    }
+
+    // Drop the newest frame and return the one before it. The oldest frame is never dropped.
+    pub fn pop(&mut self) -> Option<Vec<u8>> {
+        let group = self.groups.back_mut()?;
+        if group.deltas.pop().is_none() {
+            if self.groups.len() == 1 {
+                return Some(self.base.clone());
+            }
+            self.groups.pop_back();
+            self.base = decode(&self.groups.back()?.keyframe, &[]);
+        }
+        self.frames -= 1;
+
+        let group = self.groups.back()?;
+        Some(match group.deltas.last() {
+            Some(delta) => decode(delta, &self.base),
+            None => self.base.clone(),
+        })
+    }
+}
+
+// Length, then `state ^ base` as runs of [zeros, literal length, literal bytes].
+// Consecutive frames mostly differ in a handful of bytes, so the XOR is almost all zeros.
+fn encode(state: &[u8], base: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_varint(&mut out, state.len());
+    let xor = |i: usize| state[i] ^ base.get(i).copied().unwrap_or(0);
+
+    let mut i = 0;
+    while i < state.len() {
+        let start = i;
+        while i < state.len() && xor(i) == 0 {
+            i += 1;
+        }
+        if i == state.len() {
+            break; // Trailing zeros are implied by the length
+        }
+        write_varint(&mut out, i - start);
+
+        // A short run of zeros isn't worth breaking the literal for
+        let literal = i;
+        while i < state.len() && (xor(i) != 0 || (i + 1 < state.len() && xor(i + 1) != 0)) {
+            i += 1;
+        }
+        write_varint(&mut out, i - literal);
+        out.extend((literal..i).map(xor));
+    }
+    out
+}
+
+fn decode(delta: &[u8], base: &[u8]) -> Vec<u8> {
+    let mut pos = 0;
+    let len = read_varint(delta, &mut pos);
+    let mut state: Vec<u8> = (0..len).map(|i| base.get(i).copied().unwrap_or(0)).collect();
+
+    let mut i = 0;
+    while pos < delta.len() {
+        i += read_varint(delta, &mut pos);
+        let literal = read_varint(delta, &mut pos);
+        for byte in &delta[pos..pos + literal] {
+            state[i] ^= byte;
+            i += 1;
+        }
+        pos += literal;
+    }
+    state
+}
+
+// LEB128, runs are usually tiny
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*pos];
+        *pos += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_round_trip() {
+        let base: Vec<u8> = (0..1000).map(|i| (i * 7) as u8).collect();
+        let mut state = base.clone();
+        state[3] ^= 0xFF;
+        state[500] = 0;
+        state[501] = 1;
+        let delta = encode(&state, &base);
+        assert!(delta.len() < 16);
+        assert_eq!(decode(&delta, &base), state);
+
+        state.extend_from_slice(&[9; 300]);
+        assert_eq!(decode(&encode(&state, &base), &base), state);
+        // Shorter than the base, and against nothing like a keyframe
+        assert_eq!(decode(&encode(&base[..10], &state), &state), &base[..10]);
+        assert_eq!(decode(&encode(&state, &[]), &[]), state);
+    }
+
+    #[test]
+    fn test_push_pop_across_keyframes() {
+        let mut buffer = RewindBuffer::new(1);
+        let frames = (buffer.capacity + KEYFRAME_INTERVAL * 2) as u32;
+        for frame in 0..frames {
+            buffer.push(frame.to_le_bytes().repeat(64));
+        }
+        // Only whole groups are dropped
+        assert!(buffer.len() >= buffer.capacity && buffer.len() < buffer.capacity + KEYFRAME_INTERVAL);
+
+        let oldest = frames - buffer.len() as u32;
+        for frame in (oldest..frames - 1).rev() {
+            assert_eq!(buffer.pop(), Some(frame.to_le_bytes().repeat(64)));
+        }
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(buffer.pop(), Some(oldest.to_le_bytes().repeat(64)));
+
+        // History goes on from the frame that was rewound to
+        buffer.push(vec![1, 2, 3]);
+        assert_eq!(buffer.pop(), Some(oldest.to_le_bytes().repeat(64)));
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+
+// "DNCY", then the format version and the checksums of the cartridge it was taken from
+const MAGIC: [u8; 4] = *b"DNCY";
+pub const VERSION: u16 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    BadMagic,           // Doesn't start with "DNCY"
+    UnsupportedVersion, // Written by another version of the format
+    WrongRom,           // Header and global checksums don't match the loaded cartridge
+    Corrupt,            // Truncated, or a component doesn't fit this cartridge
+    Unavailable,        // The emulator thread is gone, nothing was loaded
+}
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            StateError::BadMagic => "Not a save state",
+            StateError::UnsupportedVersion => "Save state was made by an unsupported version",
+            StateError::WrongRom => "Save state belongs to a different ROM",
+            StateError::Corrupt => "Save state is corrupt",
+            StateError::Unavailable => "Emulator is not running",
+        };
+        f.write_str(message)
+    }
+}
+impl std::error::Error for StateError {}
+
+// Little-endian fields in the order each component writes them, no padding
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+impl StateWriter {
+    pub fn new(header_checksum: u8, global_checksum: u16) -> Self {
+        let mut writer = Self { data: Vec::new() };
+        writer.bytes(&MAGIC);
+        writer.u16(VERSION);
+        writer.u8(header_checksum);
+        writer.u16(global_checksum);
+        writer
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+    pub fn bool(&mut self, value: bool) {
+        self.data.push(value as u8);
+    }
+    pub fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn i32(&mut self, value: i32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+    // Fixed size, the reader has to know the length
+    pub fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+    // Length-prefixed, for buffers whose size depends on the cartridge
+    pub fn vec(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.bytes(bytes);
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [u8], header_checksum: u8, global_checksum: u16) -> Result<Self, StateError> {
+        let mut reader = Self { data, pos: 0 };
+        if reader.take(MAGIC.len()).map_err(|_| StateError::BadMagic)? != MAGIC {
+            return Err(StateError::BadMagic);
+        }
+        if reader.u16()? != VERSION {
+            return Err(StateError::UnsupportedVersion);
+        }
+        if reader.u8()? != header_checksum || reader.u16()? != global_checksum {
+            return Err(StateError::WrongRom);
+        }
+        Ok(reader)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        let bytes = self.data.get(self.pos..self.pos + len).ok_or(StateError::Corrupt)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+    pub fn bool(&mut self) -> Result<bool, StateError> {
+        Ok(self.u8()? != 0)
+    }
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    pub fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    pub fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    pub fn i32(&mut self) -> Result<i32, StateError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    pub fn bytes(&mut self, out: &mut [u8]) -> Result<(), StateError> {
+        out.copy_from_slice(self.take(out.len())?);
+        Ok(())
+    }
+    pub fn vec(&mut self) -> Result<Vec<u8>, StateError> {
+        let len = self.u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+    // Length-prefixed buffer that has to match the size of `out`, like cartridge RAM
+    pub fn vec_into(&mut self, out: &mut [u8]) -> Result<(), StateError> {
+        if self.u32()? as usize != out.len() {
+            return Err(StateError::Corrupt);
+        }
+        self.bytes(out)
+    }
+
+    // Trailing bytes mean the components disagree about the layout
+    pub fn finish(self) -> Result<(), StateError> {
+        if self.pos != self.data.len() {
+            return Err(StateError::Corrupt);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_header_checks() {
+        let mut writer = StateWriter::new(0x42, 0xBEEF);
+        writer.u8(0x12);
+        writer.bool(true);
+        writer.u16(0x3456);
+        writer.u32(0x789ABCDE);
+        writer.u64(u64::MAX - 1);
+        writer.i32(-8);
+        writer.vec(&[1, 2, 3]);
+        let data = writer.finish();
+
+        let mut reader = StateReader::new(&data, 0x42, 0xBEEF).unwrap();
+        assert_eq!(reader.u8(), Ok(0x12));
+        assert_eq!(reader.bool(), Ok(true));
+        assert_eq!(reader.u16(), Ok(0x3456));
+        assert_eq!(reader.u32(), Ok(0x789ABCDE));
+        assert_eq!(reader.u64(), Ok(u64::MAX - 1));
+        assert_eq!(reader.i32(), Ok(-8));
+        let mut ram = [0; 3];
+        reader.vec_into(&mut ram).unwrap();
+        assert_eq!(ram, [1, 2, 3]);
+        assert_eq!(reader.u8(), Err(StateError::Corrupt));
+        reader.finish().unwrap();
+
+        assert_eq!(StateReader::new(&data, 0x43, 0xBEEF).err(), Some(StateError::WrongRom));
+        assert_eq!(StateReader::new(&data[..2], 0x42, 0xBEEF).err(), Some(StateError::BadMagic));
+        let mut old = data.clone();
+        old[4] = 0;
+        assert_eq!(StateReader::new(&old, 0x42, 0xBEEF).err(), Some(StateError::UnsupportedVersion));
+
+        let mut reader = StateReader::new(&data, 0x42, 0xBEEF).unwrap();
+        reader.u8().unwrap();
+        assert_eq!(reader.finish(), Err(StateError::Corrupt));
+    }
+}